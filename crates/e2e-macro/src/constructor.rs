@@ -2,16 +2,22 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{FnArg, ImplItemFn, Type, spanned::Spanned as _};
 
+use crate::test_suite::suite_wrapper_name;
+
 #[derive(Debug)]
 pub(crate) struct Constructor {
     pub(crate) name: Option<String>,
     pub(crate) config_ty_name: syn::Ident,
     pub(crate) constructor_fn_name: syn::Ident,
+    /// Types of the shared resources taken as `Arc<T>` after the config.
+    pub(crate) resources: Vec<Type>,
     pub(crate) method: ImplItemFn,
 }
 
 impl Constructor {
     pub const ID: &'static str = "constructor";
+    const SIGNATURE_ERROR: &'static str = "Constructor method must take a reference to the config type, \
+        optionally followed by shared resources, e.g. `(config: &Config, db: Arc<Database>)`";
 
     pub fn new(method: ImplItemFn, attr: &syn::Attribute) -> syn::Result<Self> {
         let mut name = None;
@@ -32,31 +38,22 @@ impl Constructor {
             }
         }
 
-        let config_ty = method.sig.inputs.first().cloned().ok_or_else(|| {
-            syn::Error::new(
-                method.sig.span(),
-                "Constructor method must have a single argument for the config type",
-            )
-        })?;
+        let config_ty = method
+            .sig
+            .inputs
+            .first()
+            .cloned()
+            .ok_or_else(|| syn::Error::new(method.sig.span(), Self::SIGNATURE_ERROR))?;
         let config_ty = if let FnArg::Typed(pat_type) = config_ty {
             pat_type.ty
         } else {
-            return Err(syn::Error::new(
-                method.sig.span(),
-                "Constructor method must have a single argument for the config type",
-            ));
+            return Err(syn::Error::new(method.sig.span(), Self::SIGNATURE_ERROR));
         };
         let Type::Reference(config_ty) = *config_ty else {
-            return Err(syn::Error::new(
-                method.sig.span(),
-                "Constructor method must take a reference to the config type as an argument",
-            ));
+            return Err(syn::Error::new(method.sig.span(), Self::SIGNATURE_ERROR));
         };
         let Type::Path(config_ty) = *config_ty.elem else {
-            return Err(syn::Error::new(
-                method.sig.span(),
-                "Constructor method must take a reference to the config type as an argument",
-            ));
+            return Err(syn::Error::new(method.sig.span(), Self::SIGNATURE_ERROR));
         };
         let config_ty_name = config_ty
             .path
@@ -69,15 +66,49 @@ impl Constructor {
             })?
             .clone();
         let constructor_fn_name = method.sig.ident.clone();
+        let resources = method
+            .sig
+            .inputs
+            .iter()
+            .skip(1)
+            .map(Self::parse_resource)
+            .collect::<syn::Result<_>>()?;
 
         Ok(Self {
             name,
             config_ty_name,
             constructor_fn_name,
+            resources,
             method,
         })
     }
 
+    /// Parses a constructor parameter after the config, which must be `Arc<T>`.
+    fn parse_resource(input: &FnArg) -> syn::Result<Type> {
+        let error = || {
+            syn::Error::new(
+                input.span(),
+                "Constructor parameters after the config must be shared resources, e.g. `Arc<Database>`",
+            )
+        };
+        let FnArg::Typed(pat_type) = input else {
+            return Err(error());
+        };
+        let Type::Path(path) = &*pat_type.ty else {
+            return Err(error());
+        };
+        let Some(segment) = path.path.segments.last().filter(|s| s.ident == "Arc") else {
+            return Err(error());
+        };
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return Err(error());
+        };
+        match args.args.first() {
+            Some(syn::GenericArgument::Type(ty)) if args.args.len() == 1 => Ok(ty.clone()),
+            _ => Err(error()),
+        }
+    }
+
     pub fn render(
         &self,
         suite_name: &syn::Lit,
@@ -90,16 +121,24 @@ impl Constructor {
         let mut method = self.method.clone();
         method.sig.ident = constructor_fn_name_inner.clone();
 
+        let suite_wrapper_name = suite_wrapper_name(struct_ty_name);
         let factory_name =
             quote::format_ident!("{}Factory_{}", struct_ty_name, constructor_fn_name);
 
-        let suite_name_code = if let Some(name) = &self.name {
+        let resources = &self.resources;
+        let resources_arg = if resources.is_empty() {
+            quote! { _resources }
+        } else {
+            quote! { resources }
+        };
+
+        let constructor_variant_code = if let Some(name) = &self.name {
             quote! {
-                format!("{} ({})", #suite_name, #name)
+                Some(#name.to_string())
             }
         } else {
             quote! {
-                #suite_name.to_string()
+                None
             }
         };
 
@@ -117,13 +156,30 @@ impl Constructor {
 
             #[#crate_name::__private_reexports::async_trait]
             impl #crate_name::TestSuiteFactory<#config_ty_name> for #factory_name {
-                fn name(&self) -> String {
-                    #suite_name_code
+                fn id(&self) -> #crate_name::TestSuiteId {
+                    #crate_name::TestSuiteId::new(#suite_name, #constructor_variant_code)
+                }
+
+                fn tests_metadata(&self) -> Vec<#crate_name::TestMetadata> {
+                    #struct_ty_name::__e2e_tests_metadata()
+                }
+
+                fn resources(&self) -> Vec<#crate_name::ResourceDecl<#config_ty_name>> {
+                    vec![#(#crate_name::ResourceDecl::of::<#resources>()),*]
                 }
 
-                async fn create_suite(&self, config: &#config_ty_name) -> anyhow::Result<Box<dyn #crate_name::TestSuite>> {
-                    let self_ = #struct_ty_name::#constructor_fn_name_inner(config).await?;
-                    Ok(Box::new(self_))
+                async fn create_suite(
+                    &self,
+                    config: &#config_ty_name,
+                    #resources_arg: &#crate_name::Resources,
+                ) -> anyhow::Result<Box<dyn #crate_name::TestSuite>> {
+                    let self_ = #struct_ty_name::#constructor_fn_name_inner(
+                        config,
+                        #(resources.get::<#resources>()?),*
+                    )
+                    .await?;
+                    let shared = ::std::sync::Arc::new(#crate_name::__private_reexports::RwLock::new(self_));
+                    Ok(Box::new(#suite_wrapper_name(shared)))
                 }
             }
 
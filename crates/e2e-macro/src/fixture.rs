@@ -0,0 +1,77 @@
+use syn::{Expr, spanned::Spanned as _};
+
+#[derive(Debug)]
+pub(crate) struct Fixture {
+    pub(crate) method: syn::ImplItemFn,
+    /// Name of the method that tears the fixture down.
+    pub(crate) teardown: Option<syn::Ident>,
+    /// Method that tears the fixture down, resolved once all the methods are known.
+    pub(crate) teardown_method: Option<syn::ImplItemFn>,
+}
+
+impl Fixture {
+    pub const ID: &'static str = "fixture";
+
+    pub fn new(method: syn::ImplItemFn, attr: &syn::Attribute) -> syn::Result<Self> {
+        if !matches!(method.sig.inputs.first(), Some(syn::FnArg::Receiver(_)))
+            || method.sig.inputs.len() != 1
+        {
+            return Err(syn::Error::new(
+                method.sig.span(),
+                "Fixture methods must only take `&self` or `&mut self`",
+            ));
+        }
+
+        let mut teardown = None;
+        if let syn::Meta::List(_) = &attr.meta {
+            let arg: Expr = attr.parse_args()?;
+            let Expr::Assign(assign) = &arg else {
+                return Err(syn::Error::new(
+                    arg.span(),
+                    "Expected `teardown = method` argument in `fixture` attribute",
+                ));
+            };
+            let is_teardown =
+                matches!(&*assign.left, Expr::Path(path) if path.path.is_ident("teardown"));
+            let teardown_fn = match &*assign.right {
+                Expr::Path(path) => path.path.get_ident().cloned(),
+                _ => None,
+            };
+            if !is_teardown || teardown_fn.is_none() {
+                return Err(syn::Error::new(
+                    arg.span(),
+                    "Expected `teardown = method` argument in `fixture` attribute",
+                ));
+            }
+            teardown = teardown_fn;
+        }
+
+        Ok(Self {
+            method,
+            teardown,
+            teardown_method: None,
+        })
+    }
+
+    /// Finds the teardown method among the methods of the suite.
+    pub fn resolve_teardown(&mut self, methods: &[syn::ImplItemFn]) -> syn::Result<()> {
+        let Some(teardown) = &self.teardown else {
+            return Ok(());
+        };
+        let method = methods
+            .iter()
+            .find(|method| method.sig.ident == *teardown)
+            .ok_or_else(|| {
+                syn::Error::new(
+                    teardown.span(),
+                    format!("Teardown method `{teardown}` is not found"),
+                )
+            })?;
+        self.teardown_method = Some(method.clone());
+        Ok(())
+    }
+
+    pub fn name(&self) -> &syn::Ident {
+        &self.method.sig.ident
+    }
+}
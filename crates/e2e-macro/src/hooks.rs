@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 
+use crate::test_suite::render_shared_call;
+
 #[derive(Debug)]
 pub(crate) struct Hooks {
     hooks: HashMap<String, syn::ImplItemFn>,
@@ -49,13 +51,14 @@ impl Hooks {
         Ok(())
     }
 
-    pub fn render(&self, struct_ty_name: &syn::Ident) -> TokenStream2 {
+    pub fn render(&self) -> TokenStream2 {
         let rendered = self.hooks.iter().map(|(kind, method)| {
-            let fn_name = &method.sig.ident;
             let kind = quote::format_ident!("{}", kind);
+            let call = render_shared_call(method, quote! {});
             quote! {
                 async fn #kind(&self) -> anyhow::Result<()> {
-                    #struct_ty_name::#fn_name(&self).await
+                    let suite = &self.0;
+                    #call
                 }
             }
         });
@@ -6,6 +6,7 @@ use syn::parse_macro_input;
 use crate::test_suite::TestSuite;
 
 mod constructor;
+mod fixture;
 mod hooks;
 mod test_case;
 mod test_suite;
@@ -2,12 +2,26 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{Expr, ExprLit, Token, punctuated::Punctuated, spanned::Spanned as _};
 
+use crate::{fixture::Fixture, test_suite::render_shared_call};
+
+/// Parameter of a test case method after `&self`.
+#[derive(Debug)]
+pub(crate) enum TestParam {
+    /// `&TestContext` of the test.
+    Context,
+    /// Reference to the fixture created by the method with the same name (ignoring
+    /// leading underscores).
+    Fixture { name: syn::Ident, mutable: bool },
+}
+
 #[derive(Debug)]
 pub(crate) struct TestCase {
     pub(crate) name: String,
     pub(crate) method: syn::ImplItemFn,
     pub(crate) ignore: bool,
     pub(crate) only: bool,
+    pub(crate) tags: Vec<String>,
+    pub(crate) params: Vec<TestParam>,
 }
 
 impl TestCase {
@@ -52,8 +66,11 @@ impl TestCase {
 
         let mut ignore = false;
         let mut only = false;
+        let mut tags = Vec::new();
         for arg in arguments.iter().skip(1) {
-            if let Expr::Path(path) = arg {
+            if let Expr::Assign(assign) = arg {
+                tags = Self::parse_tags(assign)?;
+            } else if let Expr::Path(path) = arg {
                 if path.path.is_ident("ignore") {
                     ignore = true;
                 } else if path.path.is_ident("only") {
@@ -67,39 +84,247 @@ impl TestCase {
             } else {
                 return Err(syn::Error::new(
                     arg.span(),
-                    "`test_case` attribute arguments must be identifiers",
+                    "`test_case` attribute arguments must be identifiers or `tags = [..]`",
                 ));
             }
         }
 
+        let params = Self::parse_inputs(&method)?;
+
         Ok(Self {
             name,
             method,
             ignore,
             only,
+            tags,
+            params,
         })
     }
 
+    /// Parses parameters after `&self`: the test context and references to fixtures.
+    fn parse_inputs(method: &syn::ImplItemFn) -> syn::Result<Vec<TestParam>> {
+        let mut inputs = method.sig.inputs.iter();
+        if !matches!(inputs.next(), Some(syn::FnArg::Receiver(_))) {
+            return Err(syn::Error::new(
+                method.sig.span(),
+                "Test case methods must take `&self`",
+            ));
+        }
+        let mut params = Vec::new();
+        for input in inputs {
+            let syn::FnArg::Typed(pat_type) = input else {
+                unreachable!("Receiver can only be the first argument");
+            };
+            let syn::Type::Reference(reference) = &*pat_type.ty else {
+                return Err(syn::Error::new(
+                    pat_type.ty.span(),
+                    "Test case parameters must be `&TestContext` or references to fixtures",
+                ));
+            };
+            let is_context = matches!(
+                &*reference.elem,
+                syn::Type::Path(path)
+                    if path.path.segments.last().is_some_and(|s| s.ident == "TestContext")
+            );
+            if is_context {
+                params.push(TestParam::Context);
+                continue;
+            }
+            let syn::Pat::Ident(pat) = &*pat_type.pat else {
+                return Err(syn::Error::new(
+                    pat_type.pat.span(),
+                    "Fixture parameters must be named after the fixture method",
+                ));
+            };
+            // Allow `_fixture` for fixtures that are only needed for their side effects.
+            let name = pat.ident.to_string();
+            let name = syn::Ident::new(name.trim_start_matches('_'), pat.ident.span());
+            let is_duplicate = params.iter().any(
+                |param| matches!(param, TestParam::Fixture { name: other, .. } if *other == name),
+            );
+            if is_duplicate {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "Fixture is requested more than once",
+                ));
+            }
+            params.push(TestParam::Fixture {
+                name,
+                mutable: reference.mutability.is_some(),
+            });
+        }
+        Ok(params)
+    }
+
+    /// Checks that all the requested fixtures are declared.
+    pub fn check_fixtures(&self, fixtures: &[Fixture]) -> syn::Result<()> {
+        for param in &self.params {
+            if let TestParam::Fixture { name, .. } = param
+                && !fixtures.iter().any(|fixture| fixture.name() == name)
+            {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("Unknown fixture `{name}`, fixture methods must have `#[fixture]`"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the body of `Test::run`: fixtures are set up in the order of parameters and
+    /// torn down in the reverse order, regardless of the test outcome.
+    fn render_run(&self, crate_name: &syn::Ident, fixtures: &[Fixture]) -> TokenStream2 {
+        let var = |name: &syn::Ident| quote::format_ident!("__e2e_fixture_{}", name);
+        let args = self.params.iter().map(|param| match param {
+            TestParam::Context => quote! { ctx },
+            TestParam::Fixture {
+                name,
+                mutable: true,
+            } => {
+                let var = var(name);
+                quote! { #var.as_mut().unwrap() }
+            }
+            TestParam::Fixture {
+                name,
+                mutable: false,
+            } => {
+                let var = var(name);
+                quote! { #var.as_ref().unwrap() }
+            }
+        });
+        let call = render_shared_call(&self.method, quote! { #(#args),* });
+
+        let requested: Vec<_> = self
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                TestParam::Fixture { name, .. } => {
+                    fixtures.iter().find(|fixture| fixture.name() == name)
+                }
+                TestParam::Context => None,
+            })
+            .collect();
+        if requested.is_empty() {
+            return quote! {
+                let suite = &self.0;
+                #crate_name::__private_reexports::run_body(ctx, async { #call }).await
+            };
+        }
+
+        let setups = requested.iter().map(|fixture| {
+            let var = var(fixture.name());
+            let name = fixture.name().to_string();
+            let setup_call = render_shared_call(&fixture.method, quote! {});
+            quote! {
+                #[allow(unused_mut)]
+                let mut #var = if __e2e_outcome.is_ok() {
+                    match #setup_call {
+                        Ok(fixture) => Some(fixture),
+                        Err(err) => {
+                            __e2e_outcome =
+                                Err(#crate_name::__private_reexports::setup_failed(#name, err));
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+            }
+        });
+        let teardowns = requested.iter().rev().filter_map(|fixture| {
+            let teardown = fixture.teardown_method.as_ref()?;
+            let var = var(fixture.name());
+            let name = fixture.name().to_string();
+            let teardown_call = render_shared_call(teardown, quote! { fixture });
+            Some(quote! {
+                if let Some(fixture) = #var.take() {
+                    __e2e_outcome = #crate_name::__private_reexports::teardown(
+                        __e2e_outcome,
+                        #name,
+                        async move { #teardown_call },
+                    )
+                    .await;
+                }
+            })
+        });
+        quote! {
+            let suite = &self.0;
+            let mut __e2e_outcome: anyhow::Result<()> = Ok(());
+            #(#setups)*
+            if __e2e_outcome.is_ok() {
+                __e2e_outcome =
+                    #crate_name::__private_reexports::run_body(ctx, async { #call }).await;
+            }
+            #(#teardowns)*
+            __e2e_outcome
+        }
+    }
+
+    /// Parses `tags = ["tag1", "tag2"]` argument.
+    fn parse_tags(assign: &syn::ExprAssign) -> syn::Result<Vec<String>> {
+        let is_tags = matches!(&*assign.left, Expr::Path(path) if path.path.is_ident("tags"));
+        if !is_tags {
+            return Err(syn::Error::new(
+                assign.left.span(),
+                "Unknown argument in `test_case` attribute",
+            ));
+        }
+        let Expr::Array(array) = &*assign.right else {
+            return Err(syn::Error::new(
+                assign.right.span(),
+                "`tags` must be an array of string literals",
+            ));
+        };
+        array
+            .elems
+            .iter()
+            .map(|elem| match elem {
+                Expr::Lit(ExprLit {
+                    lit: syn::Lit::Str(lit_str),
+                    ..
+                }) => Ok(lit_str.value()),
+                _ => Err(syn::Error::new(
+                    elem.span(),
+                    "`tags` must be an array of string literals",
+                )),
+            })
+            .collect()
+    }
+
+    pub fn render_metadata(&self, crate_name: &syn::Ident) -> TokenStream2 {
+        let name = &self.name;
+        let ignore = self.ignore;
+        let only = self.only;
+        let tags = &self.tags;
+        quote! {
+            #crate_name::TestMetadata::new(#name)
+                .with_tags(vec![#(#tags.to_string()),*])
+                .with_ignore(#ignore)
+                .with_only(#only)
+        }
+    }
+
     pub fn render(
         &self,
         struct_ty_name: &syn::Ident,
         crate_name: &syn::Ident,
+        fixtures: &[Fixture],
     ) -> (TokenStream2, TokenStream2) {
-        let test_fn_name = &self.method.sig.ident;
         let name = &self.name;
         let ignore = self.ignore;
         let only = self.only;
+        let tags = &self.tags;
 
-        let test_ty_name = quote::format_ident!(
-            "{}_Test_{}",
-            struct_ty_name,
-            name.chars()
-                .filter(|c| c.is_alphanumeric())
-                .collect::<String>()
-        );
+        let run = self.render_run(crate_name, fixtures);
+
+        // Named after the method, since several tests may share a name.
+        let test_ty_name =
+            quote::format_ident!("{}_Test_{}", struct_ty_name, self.method.sig.ident);
         let test_case = quote! {
             #[allow(non_camel_case_types)]
-            struct #test_ty_name(#struct_ty_name);
+            struct #test_ty_name(
+                ::std::sync::Arc<#crate_name::__private_reexports::RwLock<#struct_ty_name>>,
+            );
 
             #[#crate_name::__private_reexports::async_trait]
             impl #crate_name::Test for #test_ty_name {
@@ -107,8 +332,8 @@ impl TestCase {
                     #name.to_string()
                 }
 
-                async fn run(&self) -> anyhow::Result<()> {
-                    self.0.#test_fn_name().await
+                async fn run(&self, ctx: &#crate_name::TestContext) -> anyhow::Result<()> {
+                    #run
                 }
 
                 fn ignore(&self) -> bool {
@@ -118,10 +343,14 @@ impl TestCase {
                 fn only(&self) -> bool {
                     #only
                 }
+
+                fn tags(&self) -> Vec<String> {
+                    vec![#(#tags.to_string()),*]
+                }
             }
         };
         let test_case_objects = quote! {
-            Box::new(#test_ty_name(self.clone()))
+            Box::new(#test_ty_name(self.0.clone()))
         };
         (test_case, test_case_objects)
     }
@@ -2,11 +2,35 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{ImplItem, ItemImpl, spanned::Spanned as _};
 
-use crate::{constructor::Constructor, hooks::Hooks, test_case::TestCase};
+use crate::{constructor::Constructor, fixture::Fixture, hooks::Hooks, test_case::TestCase};
+
+/// Returns the name of the suite wrapper, which shares the suite between tests.
+pub(crate) fn suite_wrapper_name(struct_ty_name: &syn::Ident) -> syn::Ident {
+    quote::format_ident!("{}_Suite", struct_ty_name)
+}
+
+/// Renders the call of a test or hook method on the suite shared as `suite`, locking it
+/// for writing if the method takes `&mut self`.
+///
+/// The guard is a temporary of the call expression, so it is held until the method returns
+/// (for a test, during the whole body), as the method borrows the suite until then.
+pub(crate) fn render_shared_call(method: &syn::ImplItemFn, args: TokenStream2) -> TokenStream2 {
+    let fn_name = &method.sig.ident;
+    let is_mut = matches!(
+        method.sig.inputs.first(),
+        Some(syn::FnArg::Receiver(receiver)) if receiver.mutability.is_some()
+    );
+    if is_mut {
+        quote! { suite.write().await.#fn_name(#args).await }
+    } else {
+        quote! { suite.read().await.#fn_name(#args).await }
+    }
+}
 
 fn is_special_attr(attr: &syn::Attribute) -> bool {
     attr.meta.path().is_ident(Constructor::ID)
         || attr.meta.path().is_ident(TestCase::ID)
+        || attr.meta.path().is_ident(Fixture::ID)
         || Hooks::ALL_HOOKS
             .iter()
             .any(|&hook| attr.meta.path().is_ident(hook))
@@ -21,6 +45,7 @@ pub(crate) struct TestSuite {
     constructors: Vec<Constructor>,
     hooks: Hooks,
     test_cases: Vec<TestCase>,
+    fixtures: Vec<Fixture>,
     cleaned_items: Vec<ImplItem>,
 }
 
@@ -46,6 +71,8 @@ impl TestSuite {
         let mut constructors = vec![];
         let mut hooks = Hooks::new();
         let mut test_cases = vec![];
+        let mut fixtures = vec![];
+        let mut methods = vec![];
 
         let mut cleaned_items = vec![];
 
@@ -84,12 +111,15 @@ impl TestSuite {
                     } else if ident == TestCase::ID {
                         let test_case = TestCase::new(method.clone(), &attr)?;
                         test_cases.push(test_case);
+                    } else if ident == Fixture::ID {
+                        fixtures.push(Fixture::new(method.clone(), &attr)?);
                     } else if Hooks::is_hook(&ident) {
                         hooks
                             .add_hook(&ident, method.clone())
                             .expect("Failed to add hook");
                     }
                 }
+                methods.push(method.clone());
                 cleaned_items.push(ImplItem::Fn(method.clone()));
             } else {
                 cleaned_items.push(item.clone());
@@ -116,6 +146,13 @@ impl TestSuite {
             }
         }
 
+        for fixture in &mut fixtures {
+            fixture.resolve_teardown(&methods)?;
+        }
+        for test_case in &test_cases {
+            test_case.check_fixtures(&fixtures)?;
+        }
+
         let crate_name = quote::format_ident!("e2e");
 
         Ok(Self {
@@ -126,6 +163,7 @@ impl TestSuite {
             hooks,
             struct_ty_name,
             test_cases,
+            fixtures,
             cleaned_items,
         })
     }
@@ -134,7 +172,8 @@ impl TestSuite {
         let mut test_case_code = Vec::new();
         let mut test_case_objects = Vec::new();
         for test_case in self.test_cases.iter() {
-            let (test_case, test_object) = test_case.render(&self.struct_ty_name, &self.crate_name);
+            let (test_case, test_object) =
+                test_case.render(&self.struct_ty_name, &self.crate_name, &self.fixtures);
             test_case_code.push(test_case);
             test_case_objects.push(test_object);
         }
@@ -156,11 +195,32 @@ impl TestSuite {
         let crate_name = &self.crate_name;
         let struct_ty_name = &self.struct_ty_name;
 
-        let hooks = self.hooks.render(struct_ty_name);
+        let suite_wrapper_name = suite_wrapper_name(struct_ty_name);
+        let hooks = self.hooks.render();
+        let tests_metadata = self
+            .test_cases
+            .iter()
+            .map(|test_case| test_case.render_metadata(crate_name));
 
         quote! {
+            impl #struct_ty_name {
+                #[doc(hidden)]
+                pub fn __e2e_tests_metadata() -> Vec<#crate_name::TestMetadata> {
+                    vec![
+                        #(#tests_metadata),*
+                    ]
+                }
+            }
+
+            /// Suite shared by its tests, so that state written by hooks and tests taking
+            /// `&mut self` is visible to the following ones.
+            #[allow(non_camel_case_types)]
+            struct #suite_wrapper_name(
+                ::std::sync::Arc<#crate_name::__private_reexports::RwLock<#struct_ty_name>>,
+            );
+
             #[#crate_name::__private_reexports::async_trait]
-            impl #crate_name::TestSuite for #struct_ty_name {
+            impl #crate_name::TestSuite for #suite_wrapper_name {
                 fn tests(&self) -> Vec<Box<dyn #crate_name::Test>> {
                     vec![
                         #(#test_case_objects),*
@@ -1,12 +1,12 @@
 use clap::Parser;
-use e2e::test_suite;
+use e2e::{TestContext, test_suite};
 
 #[derive(Debug, Clone)]
 struct TestConfig {
     value: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct TestFlow {
     value: u32,
 }
@@ -47,8 +47,9 @@ impl TestFlow {
         Ok(())
     }
 
-    #[test_case("Test case 1")]
-    async fn test_case_1(&self) -> anyhow::Result<()> {
+    #[test_case("Test case 1", tags = ["smoke"])]
+    async fn test_case_1(&self, ctx: &TestContext) -> anyhow::Result<()> {
+        tracing::info!("Running {} (iteration {})", ctx.id(), ctx.iteration());
         assert_eq!(self.value, 42);
         Ok(())
     }
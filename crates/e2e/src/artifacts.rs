@@ -0,0 +1,131 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::scope;
+
+/// File attached to a test or suite result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Artifact {
+    /// Name the artifact was attached with.
+    pub name: String,
+    /// Location of the stored artifact.
+    pub path: PathBuf,
+}
+
+/// Artifacts attached by a test or a suite, stored in a single directory.
+#[derive(Debug)]
+pub(crate) struct ArtifactStore {
+    dir: PathBuf,
+    artifacts: Mutex<Vec<Artifact>>,
+}
+
+impl ArtifactStore {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            artifacts: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn attach_bytes(&self, name: &str, bytes: &[u8]) -> io::Result<PathBuf> {
+        self.store(name, |path| fs::write(path, bytes))
+    }
+
+    pub(crate) fn attach_file(&self, name: &str, source: &Path) -> io::Result<PathBuf> {
+        self.store(name, |path| fs::copy(source, path).map(drop))
+    }
+
+    /// Returns the attached artifacts, leaving the store empty.
+    pub(crate) fn take(&self) -> Vec<Artifact> {
+        std::mem::take(&mut *self.artifacts.lock().unwrap())
+    }
+
+    fn store(
+        &self,
+        name: &str,
+        write: impl FnOnce(&Path) -> io::Result<()>,
+    ) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(file_name(name));
+        write(&path)?;
+        let mut artifacts = self.artifacts.lock().unwrap();
+        artifacts.retain(|artifact| artifact.path != path);
+        artifacts.push(Artifact {
+            name: name.to_string(),
+            path: path.clone(),
+        });
+        Ok(path)
+    }
+}
+
+/// Directory for the artifacts of the run or of a suite, next to the directories of its
+/// suites or tests. [`file_name`] never produces it, so it doesn't collide with them.
+pub(crate) const OWN_ARTIFACTS_DIR: &str = "@artifacts";
+
+/// Converts an arbitrary name (e.g. a test id) to a valid file name.
+///
+/// The name never refers to the parent or the current directory, so that the file stays
+/// within the directory it's joined to.
+pub(crate) fn file_name(name: &str) -> String {
+    if matches!(name, "" | "." | "..") {
+        return "_".repeat(name.len().max(1));
+    }
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "-_.".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Returns the path of a suite or a test relative to the run directories.
+pub(crate) fn relative_path(names: &[&str], iteration: usize) -> PathBuf {
+    let mut path: PathBuf = names.iter().map(|name| file_name(name)).collect();
+    if iteration > 1 {
+        path.as_mut_os_string().push(format!("-{iteration}"));
+    }
+    path
+}
+
+/// Attaches the bytes to the current test, or to the current suite when called from the
+/// constructor or `before_all`/`after_all` hooks, and returns the path they're stored at.
+///
+/// Artifacts are stored in a per-run directory, with a subdirectory for each suite and
+/// test; artifacts of a suite are stored in its `@artifacts` subdirectory. Attaching an
+/// artifact with the same name replaces it.
+///
+/// # Panics
+///
+/// Panics if called outside of a test suite.
+pub fn attach_bytes(name: &str, bytes: impl AsRef<[u8]>) -> io::Result<PathBuf> {
+    scope::current("attach_bytes", |scope| {
+        scope.artifacts().attach_bytes(name, bytes.as_ref())
+    })
+}
+
+/// Attaches the text (e.g. an HTTP transcript) like [`attach_bytes`].
+///
+/// # Panics
+///
+/// Panics if called outside of a test suite.
+pub fn attach_text(name: &str, text: impl AsRef<str>) -> io::Result<PathBuf> {
+    attach_bytes(name, text.as_ref())
+}
+
+/// Copies the file (e.g. a database dump) to the artifacts like [`attach_bytes`].
+///
+/// # Panics
+///
+/// Panics if called outside of a test suite.
+pub fn attach_file(name: &str, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    scope::current("attach_file", |scope| {
+        scope.artifacts().attach_file(name, path.as_ref())
+    })
+}
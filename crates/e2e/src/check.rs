@@ -0,0 +1,135 @@
+use std::{fmt, future::Future, panic::AssertUnwindSafe};
+
+use futures::FutureExt as _;
+
+use crate::{fixture, scope};
+
+/// Records a failed soft assertion of the current test, or of the current suite when
+/// called from the constructor or `before_all`/`after_all` hooks.
+///
+/// # Panics
+///
+/// Panics if called outside of a test suite.
+#[doc(hidden)]
+pub fn record_failure(message: fmt::Arguments<'_>, file: &str, line: u32) {
+    let message = format!("{message} at {file}:{line}");
+    tracing::error!("{message}");
+    scope::try_current(|scope| scope.record_failed_check(message)).unwrap_or_else(|| {
+        panic!("`e2e::check!` and `e2e::check_eq!` must be used in a test suite")
+    });
+}
+
+/// Runs a hook or the constructor of a suite, failing it if it panics or any soft
+/// assertion fails in the meantime.
+pub(crate) async fn run<T>(future: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+    let result = AssertUnwindSafe(future)
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|payload| Err(fixture::panic_error(payload)));
+    merge(result, take_failed())
+}
+
+/// Takes the soft assertions failed in the current scope since the last call.
+pub(crate) fn take_failed() -> Vec<String> {
+    scope::try_current(scope::Scope::take_failed_checks).unwrap_or_default()
+}
+
+/// Fails the test if any soft assertion failed, keeping the original error if the test
+/// failed on its own.
+pub(crate) fn merge<T>(result: anyhow::Result<T>, failed_checks: Vec<String>) -> anyhow::Result<T> {
+    if failed_checks.is_empty() {
+        return result;
+    }
+    let mut summary = format!("{} check(s) failed:", failed_checks.len());
+    for check in &failed_checks {
+        summary.push_str("\n  - ");
+        summary.push_str(check);
+    }
+    match result {
+        Ok(_) => Err(anyhow::Error::msg(summary)),
+        Err(err) => Err(err.context(summary)),
+    }
+}
+
+/// Soft assertion: records a failure of the current test if the condition is false, but
+/// lets the test continue, so that a single run reports all the mismatches.
+///
+/// The test fails once it finishes if any check failed. Checks may also be used in hooks,
+/// which fail the same way, and in tasks spawned with [`spawn`](crate::spawn), whose
+/// failures are attributed to the owner of the task. Returns the value of the condition.
+/// A custom failure message can be provided like in [`assert!`].
+///
+/// ```no_run
+/// # struct User { name: String, active: bool }
+/// # fn example(user: User) {
+/// e2e::check!(user.active);
+/// e2e::check!(!user.name.is_empty(), "User has no name");
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// Panics if the check fails outside of a test suite.
+#[macro_export]
+macro_rules! check {
+    ($cond:expr $(,)?) => {
+        $crate::check!($cond, "Check `{}` failed", ::core::stringify!($cond))
+    };
+    ($cond:expr, $($arg:tt)+) => {{
+        let passed: bool = $cond;
+        if !passed {
+            $crate::__private_reexports::record_failure(
+                ::core::format_args!($($arg)+),
+                ::core::file!(),
+                ::core::line!(),
+            );
+        }
+        passed
+    }};
+}
+
+/// Soft assertion of equality, like [`check!`] but reporting both values on failure.
+///
+/// ```no_run
+/// # struct Response { status: u16, body: String }
+/// # fn example(response: Response) {
+/// e2e::check_eq!(response.status, 200);
+/// e2e::check_eq!(response.body, "OK", "Unexpected body");
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// Panics if the check fails outside of a test suite.
+#[macro_export]
+macro_rules! check_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::check_eq!(
+            $left,
+            $right,
+            "Check `{} == {}` failed",
+            ::core::stringify!($left),
+            ::core::stringify!($right)
+        )
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let passed = *left == *right;
+                if !passed {
+                    $crate::__private_reexports::record_failure(
+                        ::core::format_args!(
+                            "{}: {:?} != {:?}",
+                            ::core::format_args!($($arg)+),
+                            left,
+                            right
+                        ),
+                        ::core::file!(),
+                        ::core::line!(),
+                    );
+                }
+                passed
+            }
+        }
+    };
+}
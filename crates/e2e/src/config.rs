@@ -1,4 +1,6 @@
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
+
+use crate::{ListFormat, RepeatMode, Shard};
 
 #[derive(Debug, Default, Clone, clap::Args)]
 pub struct TestRunnerConfiguration {
@@ -17,10 +19,71 @@ pub struct TestRunnerConfiguration {
     /// Stop after the first failed test.
     #[clap(long)]
     pub(crate) fail_fast: bool,
+    /// Directory to store the results of each run in.
+    #[clap(long)]
+    pub(crate) history_dir: Option<PathBuf>,
+    /// Print the analysis of the runs stored in the history directory instead of running tests.
+    #[clap(long, requires = "history_dir")]
+    pub(crate) history_report: bool,
+    /// Identifier of the run to look for newly failing tests since (previous run by default).
+    #[clap(long, requires = "history_report")]
+    pub(crate) history_since: Option<String>,
+    /// Only run tests that failed in the last run stored in the history directory.
+    #[clap(long, requires = "history_dir")]
+    pub(crate) rerun_failed: bool,
+    /// List test suites and tests without running them.
+    #[clap(long)]
+    pub(crate) list: bool,
+    /// Output format for `--list`.
+    #[clap(long, value_enum, default_value_t)]
+    pub(crate) list_format: ListFormat,
+    /// Only run the suites assigned to this shard, specified as K/N (e.g. 1/4).
+    #[clap(long)]
+    pub(crate) shard: Option<Shard>,
+    /// Results of a previous run (e.g. a file from the history directory) used to balance
+    /// shards by duration.
+    #[clap(long, requires = "shard")]
+    pub(crate) shard_durations: Option<PathBuf>,
+    /// Run suites and tests within suites in random order.
+    #[clap(long)]
+    pub(crate) shuffle: bool,
+    /// Seed for `--shuffle`, random by default.
+    #[clap(long, requires = "shuffle")]
+    pub(crate) seed: Option<u64>,
+    /// Run each selected suite the specified number of times.
+    #[clap(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub(crate) repeat: Option<usize>,
+    /// Repeat suites until the first failure (up to `--repeat` times, if specified;
+    /// otherwise a single suite must be selected, as it's repeated indefinitely).
+    #[clap(long)]
+    pub(crate) until_failure: bool,
+    /// Whether each iteration creates a new suite or reuses the same one.
+    #[clap(long, value_enum, default_value_t)]
+    pub(crate) repeat_mode: RepeatMode,
+    /// Time given to teardown hooks to finish once the run is interrupted.
+    #[clap(long)]
+    pub(crate) cancellation_grace_period_ms: Option<u64>,
+    /// Time given to tasks spawned with `e2e::spawn` to finish after their test or suite.
+    #[clap(long)]
+    pub(crate) spawned_tasks_timeout_ms: Option<u64>,
+    /// Time given to fixtures to be torn down once the test body finishes or times out.
+    #[clap(long)]
+    pub(crate) teardown_timeout_ms: Option<u64>,
+    /// Directory to store test artifacts in, with a subdirectory for each run (system
+    /// temporary directory by default).
+    #[clap(long)]
+    pub(crate) artifacts_dir: Option<PathBuf>,
+    /// Keep temporary directories of suites and tests that passed (the ones of failed
+    /// suites and tests are always kept).
+    #[clap(long)]
+    pub(crate) keep_temp: bool,
 }
 
 impl TestRunnerConfiguration {
     const DEFAULT_TIMEOUT_MS: u64 = 60_000; // 60 seconds
+    const DEFAULT_CANCELLATION_GRACE_PERIOD_MS: u64 = 10_000; // 10 seconds
+    const DEFAULT_SPAWNED_TASKS_TIMEOUT_MS: u64 = 1_000; // 1 second
+    const DEFAULT_TEARDOWN_TIMEOUT_MS: u64 = 10_000; // 10 seconds
 
     pub fn with_test_suite_filter(mut self, filter: regex::Regex) -> Self {
         self.test_suite_filter = Some(filter);
@@ -42,6 +105,19 @@ impl TestRunnerConfiguration {
         self
     }
 
+    /// Number of iterations for each suite.
+    pub(crate) fn iterations(&self) -> usize {
+        match (self.repeat, self.until_failure) {
+            (Some(repeat), _) => repeat,
+            (None, true) => usize::MAX,
+            (None, false) => 1,
+        }
+    }
+
+    pub(crate) fn is_repeated(&self) -> bool {
+        self.repeat.is_some() || self.until_failure
+    }
+
     pub fn timeout(&self) -> Duration {
         Duration::from_millis(self.timeout_ms.unwrap_or(Self::DEFAULT_TIMEOUT_MS))
     }
@@ -50,4 +126,126 @@ impl TestRunnerConfiguration {
         self.fail_fast = fail_fast;
         self
     }
+
+    pub fn with_history_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.history_dir = Some(dir.into());
+        self
+    }
+
+    pub fn with_history_report(mut self, history_report: bool) -> Self {
+        self.history_report = history_report;
+        self
+    }
+
+    pub fn with_history_since(mut self, run_id: impl Into<String>) -> Self {
+        self.history_since = Some(run_id.into());
+        self
+    }
+
+    pub fn with_rerun_failed(mut self, rerun_failed: bool) -> Self {
+        self.rerun_failed = rerun_failed;
+        self
+    }
+
+    pub fn with_list(mut self, list: bool) -> Self {
+        self.list = list;
+        self
+    }
+
+    pub fn with_list_format(mut self, format: ListFormat) -> Self {
+        self.list_format = format;
+        self
+    }
+
+    pub fn with_shard(mut self, shard: Shard) -> Self {
+        self.shard = Some(shard);
+        self
+    }
+
+    pub fn with_shard_durations(mut self, path: impl Into<PathBuf>) -> Self {
+        self.shard_durations = Some(path.into());
+        self
+    }
+
+    pub fn with_shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: usize) -> Self {
+        self.repeat = Some(repeat);
+        self
+    }
+
+    pub fn with_until_failure(mut self, until_failure: bool) -> Self {
+        self.until_failure = until_failure;
+        self
+    }
+
+    pub fn with_repeat_mode(mut self, mode: RepeatMode) -> Self {
+        self.repeat_mode = mode;
+        self
+    }
+
+    pub fn cancellation_grace_period(&self) -> Duration {
+        Duration::from_millis(
+            self.cancellation_grace_period_ms
+                .unwrap_or(Self::DEFAULT_CANCELLATION_GRACE_PERIOD_MS),
+        )
+    }
+
+    pub fn with_cancellation_grace_period(mut self, grace_period: Duration) -> Self {
+        self.cancellation_grace_period_ms = Some(grace_period.as_millis() as u64);
+        self
+    }
+
+    pub fn spawned_tasks_timeout(&self) -> Duration {
+        Duration::from_millis(
+            self.spawned_tasks_timeout_ms
+                .unwrap_or(Self::DEFAULT_SPAWNED_TASKS_TIMEOUT_MS),
+        )
+    }
+
+    pub fn with_spawned_tasks_timeout(mut self, timeout: Duration) -> Self {
+        self.spawned_tasks_timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn teardown_timeout(&self) -> Duration {
+        Duration::from_millis(
+            self.teardown_timeout_ms
+                .unwrap_or(Self::DEFAULT_TEARDOWN_TIMEOUT_MS),
+        )
+    }
+
+    pub fn with_teardown_timeout(mut self, timeout: Duration) -> Self {
+        self.teardown_timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn with_artifacts_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.artifacts_dir = Some(dir.into());
+        self
+    }
+
+    pub fn with_keep_temp(mut self, keep_temp: bool) -> Self {
+        self.keep_temp = keep_temp;
+        self
+    }
+
+    /// Directory to store artifacts of all the runs in.
+    pub(crate) fn artifacts_dir(&self) -> PathBuf {
+        let dir = self
+            .artifacts_dir
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("e2e-artifacts"));
+        // Artifact paths are referenced by reports, so they must not depend on the
+        // working directory.
+        std::path::absolute(&dir).unwrap_or(dir)
+    }
 }
@@ -0,0 +1,115 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{TestId, artifacts::ArtifactStore, scope::Scope, temp_dir::TempDir};
+
+/// Context of a running test, passed to test methods that accept `&TestContext`.
+///
+/// ```ignore
+/// #[test_case("Uses context")]
+/// async fn uses_context(&self, ctx: &TestContext) -> anyhow::Result<()> {
+///     let config = ctx.temp_dir()?.join("config.toml");
+///     // ...
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TestContext {
+    id: TestId,
+    iteration: usize,
+    cancellation: CancellationToken,
+    span: tracing::Span,
+    /// Deadline of the test body along with the timeout it was computed from.
+    deadline: Arc<OnceLock<(Instant, Duration)>>,
+    /// Cancelled once the test body finishes, before the fixtures are torn down.
+    body_finished: CancellationToken,
+    temp_dir: Arc<TempDir>,
+    artifacts: Arc<ArtifactStore>,
+}
+
+impl TestContext {
+    pub(crate) fn new(id: TestId, iteration: usize, scope: &Scope) -> Self {
+        let span = tracing::info_span!("test", id = %id, iteration);
+        Self {
+            id,
+            iteration,
+            cancellation: scope.token().clone(),
+            span,
+            deadline: scope.deadline().clone(),
+            body_finished: CancellationToken::new(),
+            temp_dir: scope.temp_dir().clone(),
+            artifacts: scope.artifacts().clone(),
+        }
+    }
+
+    pub fn id(&self) -> &TestId {
+        &self.id
+    }
+
+    /// 1-based number of the attempt to run the test when tests are repeated.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Token that is cancelled once the test finishes, times out or the run is interrupted.
+    ///
+    /// Same as [`crate::cancellation_token`] called from the test.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Time by which the test body must finish, once it has started.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline.get().map(|(deadline, _)| *deadline)
+    }
+
+    pub(crate) fn start_timer(&self, timeout: Duration) {
+        let _ = self.deadline.set((Instant::now() + timeout, timeout));
+    }
+
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.deadline.get().map(|(_, timeout)| *timeout)
+    }
+
+    pub(crate) fn finish_body(&self) {
+        self.body_finished.cancel();
+    }
+
+    pub(crate) async fn body_finished(&self) {
+        self.body_finished.cancelled().await
+    }
+
+    /// Span the test runs in.
+    ///
+    /// Events emitted by the test and by the tasks it spawns with [`crate::spawn`] are
+    /// recorded within it. Only the logs of those tasks are captured into the test result.
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Returns the temporary directory of the test, see [`crate::temp_dir`].
+    pub fn temp_dir(&self) -> io::Result<&Path> {
+        self.temp_dir.get()
+    }
+
+    /// Attaches the bytes to the test result, see [`crate::attach_bytes`].
+    pub fn attach_bytes(&self, name: &str, bytes: impl AsRef<[u8]>) -> io::Result<PathBuf> {
+        self.artifacts.attach_bytes(name, bytes.as_ref())
+    }
+
+    /// Attaches the text to the test result, see [`crate::attach_text`].
+    pub fn attach_text(&self, name: &str, text: impl AsRef<str>) -> io::Result<PathBuf> {
+        self.attach_bytes(name, text.as_ref())
+    }
+
+    /// Copies the file to the test artifacts, see [`crate::attach_file`].
+    pub fn attach_file(&self, name: &str, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        self.artifacts.attach_file(name, path.as_ref())
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    fmt,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::scope;
+
+/// Error returned by [`eventually`] and [`assert_eventually!`](crate::assert_eventually)
+/// when the condition is not met in time.
+#[derive(Debug)]
+pub struct EventuallyError {
+    attempts: usize,
+    elapsed: Duration,
+    last_error: anyhow::Error,
+    cancelled: bool,
+}
+
+impl EventuallyError {
+    /// Number of times the condition was checked.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Time spent checking the condition.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Error of the last check.
+    pub fn last_error(&self) -> &anyhow::Error {
+        &self.last_error
+    }
+
+    /// Whether checking was stopped because the test was cancelled.
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl fmt::Display for EventuallyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = if self.cancelled {
+            "Condition check was cancelled"
+        } else {
+            "Condition was not met"
+        };
+        write!(
+            f,
+            "{outcome} after {} attempt(s) in {:?}, last error: {:#}",
+            self.attempts, self.elapsed, self.last_error
+        )
+    }
+}
+
+impl std::error::Error for EventuallyError {}
+
+/// State of repeated checks of a condition, shared by [`eventually`] and
+/// [`assert_eventually!`](crate::assert_eventually).
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Retry {
+    started_at: Instant,
+    deadline: Instant,
+    interval: Duration,
+    attempts: usize,
+    cancellation: Option<CancellationToken>,
+}
+
+impl Retry {
+    /// Starts checking; the checks end at the test deadline if it comes before `timeout`.
+    pub fn new(timeout: Duration, interval: Duration) -> Self {
+        let started_at = Instant::now();
+        let (test_deadline, cancellation) = scope::try_current(|scope| {
+            let deadline = scope.deadline().get().map(|(deadline, _)| *deadline);
+            (deadline, scope.token().clone())
+        })
+        .unzip();
+        let mut deadline = started_at + timeout;
+        if let Some(test_deadline) = test_deadline.flatten() {
+            deadline = deadline.min(test_deadline);
+        }
+        Self {
+            started_at,
+            deadline,
+            interval,
+            attempts: 0,
+            cancellation,
+        }
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Runs an attempt, failing it if it doesn't finish by the deadline.
+    pub async fn attempt<T>(
+        &self,
+        attempt: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        tokio::time::timeout_at(self.deadline.into(), attempt)
+            .await
+            .unwrap_or_else(|_| {
+                Err(anyhow::format_err!(
+                    "Attempt did not finish before the deadline"
+                ))
+            })
+    }
+
+    /// Records the failed attempt and waits for the next one, failing if there's no
+    /// time left or the test was cancelled.
+    pub async fn retry(&mut self, err: anyhow::Error) -> Result<(), EventuallyError> {
+        self.attempts += 1;
+        let now = Instant::now();
+        if now >= self.deadline {
+            return Err(self.error(err, false));
+        }
+        let next_attempt = (now + self.interval).min(self.deadline);
+        let cancelled = async {
+            match &self.cancellation {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = tokio::time::sleep_until(next_attempt.into()) => Ok(()),
+            _ = cancelled => Err(self.error(err, true)),
+        }
+    }
+
+    fn error(&self, last_error: anyhow::Error, cancelled: bool) -> EventuallyError {
+        EventuallyError {
+            attempts: self.attempts,
+            elapsed: self.started_at.elapsed(),
+            last_error,
+            cancelled,
+        }
+    }
+}
+
+/// Checks the condition every `interval` until it succeeds, returning its value.
+///
+/// Checking stops after `timeout`, or earlier if the test deadline comes first or the
+/// test is cancelled, and the error reports the number of attempts, the elapsed time and
+/// the error of the last attempt.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # async fn order_status() -> anyhow::Result<String> { todo!() }
+/// # async fn example() -> anyhow::Result<()> {
+/// let status = e2e::eventually(Duration::from_secs(5), Duration::from_millis(100), || async {
+///     let status = order_status().await?;
+///     anyhow::ensure!(status != "pending", "Order is still pending");
+///     Ok(status)
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn eventually<T, F, Fut>(
+    timeout: Duration,
+    interval: Duration,
+    mut check: F,
+) -> Result<T, EventuallyError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut retry = Retry::new(timeout, interval);
+    loop {
+        match retry.attempt(check()).await {
+            Ok(value) => return Ok(value),
+            Err(err) => retry.retry(err).await?,
+        }
+    }
+}
+
+/// Checks the boolean condition every `interval` until it's true, returning
+/// [`EventuallyError`] from the enclosing function otherwise.
+///
+/// The condition may `.await` and borrow local variables; each check is bounded by the
+/// deadline. A custom failure message can be provided like in [`anyhow::ensure!`]. See
+/// [`eventually`] for the timing rules.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # async fn queue_len() -> usize { 0 }
+/// # async fn example() -> anyhow::Result<()> {
+/// e2e::assert_eventually!(
+///     Duration::from_secs(5),
+///     Duration::from_millis(100),
+///     queue_len().await == 0,
+///     "Queue is not drained"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_eventually {
+    ($timeout:expr, $interval:expr, $cond:expr $(,)?) => {
+        $crate::assert_eventually!(
+            $timeout,
+            $interval,
+            $cond,
+            "Condition `{}` is false",
+            ::core::stringify!($cond)
+        )
+    };
+    ($timeout:expr, $interval:expr, $cond:expr, $($arg:tt)+) => {{
+        let mut retry = $crate::__private_reexports::Retry::new($timeout, $interval);
+        loop {
+            let attempt = retry.attempt(async {
+                if $cond {
+                    ::core::result::Result::Ok(())
+                } else {
+                    ::core::result::Result::Err($crate::__private_reexports::anyhow::format_err!($($arg)+))
+                }
+            });
+            let ::core::result::Result::Err(err) = attempt.await else {
+                break;
+            };
+            if let ::core::result::Result::Err(err) = retry.retry(err).await {
+                return ::core::result::Result::Err(::core::convert::From::from(err));
+            }
+        }
+    }};
+}
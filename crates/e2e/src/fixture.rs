@@ -0,0 +1,89 @@
+//! Support for running test bodies, used by the code generated for tests.
+
+use std::{any::Any, future::Future, panic::AssertUnwindSafe};
+
+use futures::FutureExt;
+
+use crate::TestContext;
+
+/// Converts the payload of a caught panic to an error.
+pub(crate) fn panic_error(payload: Box<dyn Any + Send>) -> anyhow::Error {
+    if let Some(err) = payload.downcast_ref::<String>() {
+        anyhow::format_err!("Test panicked with message: {}", err)
+    } else if let Some(err) = payload.downcast_ref::<&str>() {
+        anyhow::format_err!("Test panicked with message: {}", err)
+    } else {
+        anyhow::format_err!("Test panicked with an unknown error type")
+    }
+}
+
+pub(crate) fn timeout_error(timeout: std::time::Duration) -> anyhow::Error {
+    anyhow::format_err!("Test timed out after {:?}", timeout)
+}
+
+pub(crate) fn teardown_timeout_error(timeout: std::time::Duration) -> anyhow::Error {
+    anyhow::format_err!("Fixture teardown timed out after {:?}", timeout)
+}
+
+async fn catch_unwind(future: impl Future<Output = anyhow::Result<()>>) -> anyhow::Result<()> {
+    AssertUnwindSafe(future)
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|payload| Err(panic_error(payload)))
+}
+
+/// Runs the test body, converting panics and timeouts to errors, so that fixtures can be
+/// torn down afterwards.
+///
+/// Lets the runner know once the body finishes in time, so that the teardown that follows
+/// is given its own time. The body is stopped if the run is interrupted.
+pub async fn run_body(
+    ctx: &TestContext,
+    body: impl Future<Output = anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    let body = async {
+        tokio::select! {
+            res = catch_unwind(body) => res,
+            _ = ctx.cancellation_token().cancelled() => {
+                Err(anyhow::format_err!("Run was interrupted"))
+            }
+        }
+    };
+    let result = match (ctx.deadline(), ctx.timeout()) {
+        (Some(deadline), Some(timeout)) => {
+            match tokio::time::timeout_at(deadline.into(), body).await {
+                Ok(result) => result,
+                Err(_) => return Err(timeout_error(timeout)),
+            }
+        }
+        _ => body.await,
+    };
+    ctx.finish_body();
+    result
+}
+
+pub fn setup_failed(name: &str, err: anyhow::Error) -> anyhow::Error {
+    err.context(format!("Failed to set up fixture `{name}`"))
+}
+
+/// Awaits the fixture teardown and merges its result into the test outcome.
+///
+/// If the test has already failed, the teardown error is logged, so that the original
+/// failure is reported.
+pub async fn teardown(
+    outcome: anyhow::Result<()>,
+    name: &str,
+    teardown: impl Future<Output = anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    let result = catch_unwind(teardown)
+        .await
+        .map_err(|err| err.context(format!("Failed to tear down fixture `{name}`")));
+    match (outcome, result) {
+        (Ok(()), result) => result,
+        (Err(err), Ok(())) => Err(err),
+        (Err(err), Err(teardown_err)) => {
+            tracing::error!("{:#}", teardown_err);
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use crate::{TestId, TestResult, TestSuiteId, TestSuiteResult};
+
+/// Outcome of a single test in the persisted history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Ignored,
+    /// The run was interrupted before the test finished.
+    Cancelled,
+}
+
+impl TestStatus {
+    /// Whether the test ran to completion.
+    pub fn executed(&self) -> bool {
+        matches!(self, Self::Passed | Self::Failed)
+    }
+}
+
+impl TestStatus {
+    /// Priority of the status when merging the outcomes of a repeated test: any failure
+    /// makes the test failed.
+    fn severity(&self) -> u8 {
+        match self {
+            Self::Ignored => 0,
+            Self::Passed => 1,
+            Self::Cancelled => 2,
+            Self::Failed => 3,
+        }
+    }
+}
+
+impl From<&TestResult> for TestStatus {
+    fn from(result: &TestResult) -> Self {
+        if result.ignored {
+            Self::Ignored
+        } else if result.cancelled() {
+            Self::Cancelled
+        } else if result.passed() {
+            Self::Passed
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+/// Outcome of a test in a run.
+///
+/// If the test was repeated, its outcomes are merged into a single record: the test is
+/// failed if any attempt failed, and the duration is averaged over the executed attempts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TestRecord {
+    pub id: TestId,
+    pub status: TestStatus,
+    pub duration_ms: u64,
+    /// Number of times the test was run.
+    #[serde(default = "one")]
+    pub attempts: usize,
+    /// 1-based iteration the status comes from, e.g. the first failed one.
+    #[serde(default = "one")]
+    pub iteration: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+impl TestRecord {
+    fn merge(results: &[&TestResult]) -> Self {
+        let (status, iteration) = results
+            .iter()
+            .map(|test| (TestStatus::from(*test), test.iteration))
+            // The earliest iteration wins among equally severe statuses.
+            .max_by_key(|(status, iteration)| (status.severity(), std::cmp::Reverse(*iteration)))
+            .expect("Test must have at least one result");
+        let executed: Vec<_> = results
+            .iter()
+            .filter(|test| TestStatus::from(**test).executed())
+            .collect();
+        let duration_ms = match executed.len() {
+            0 => 0,
+            n => {
+                executed
+                    .iter()
+                    .map(|test| test.duration.as_millis() as u64)
+                    .sum::<u64>()
+                    / n as u64
+            }
+        };
+        Self {
+            id: results[0].id.clone(),
+            status,
+            duration_ms,
+            attempts: results.iter().filter(|test| !test.ignored).count(),
+            iteration,
+        }
+    }
+}
+
+/// Outcome of a suite in a run, with the results of its iterations merged if the suite
+/// was repeated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TestSuiteRecord {
+    pub id: TestSuiteId,
+    pub passed: bool,
+    /// Phase of the suite-level failure (e.g. `before_all`), if any.
+    pub error_phase: Option<String>,
+    pub duration_ms: u64,
+    pub tests: Vec<TestRecord>,
+}
+
+impl TestSuiteRecord {
+    fn merge(results: &[&TestSuiteResult]) -> Self {
+        let mut tests: Vec<(&TestId, Vec<&TestResult>)> = Vec::new();
+        for test in results.iter().flat_map(|suite| &suite.tests) {
+            match tests.iter_mut().find(|(id, _)| **id == test.id) {
+                Some((_, attempts)) => attempts.push(test),
+                None => tests.push((&test.id, vec![test])),
+            }
+        }
+        let duration_ms = results
+            .iter()
+            .map(|suite| suite.duration.as_millis() as u64)
+            .sum::<u64>()
+            / results.len() as u64;
+        Self {
+            id: results[0].id.clone(),
+            passed: results.iter().all(|suite| suite.passed),
+            error_phase: results
+                .iter()
+                .find_map(|suite| suite.error.as_ref())
+                .map(|err| err.phase().to_string()),
+            duration_ms,
+            tests: tests
+                .iter()
+                .map(|(_, attempts)| TestRecord::merge(attempts))
+                .collect(),
+        }
+    }
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Returns the identifier of the run started at the specified time.
+pub(crate) fn run_id(started_at: SystemTime) -> String {
+    format!("run-{}", millis_since_epoch(started_at))
+}
+
+/// Results of a single run, as stored in the history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RunRecord {
+    /// Identifier of the run, which is also the name of the file it's stored in.
+    pub run_id: String,
+    /// Start of the run, in milliseconds since UNIX epoch.
+    pub started_at_ms: u64,
+    pub suites: Vec<TestSuiteRecord>,
+}
+
+impl RunRecord {
+    pub fn new(started_at: SystemTime, results: &[TestSuiteResult]) -> Self {
+        let started_at_ms = millis_since_epoch(started_at);
+        // Repeated suites are reported once per iteration, but recorded once per run.
+        let mut suites: Vec<(&TestSuiteId, Vec<&TestSuiteResult>)> = Vec::new();
+        for suite in results {
+            match suites.iter_mut().find(|(id, _)| **id == suite.id) {
+                Some((_, iterations)) => iterations.push(suite),
+                None => suites.push((&suite.id, vec![suite])),
+            }
+        }
+        let suites = suites
+            .iter()
+            .map(|(_, iterations)| TestSuiteRecord::merge(iterations))
+            .collect();
+        Self {
+            run_id: run_id(started_at),
+            started_at_ms,
+            suites,
+        }
+    }
+
+    pub fn tests(&self) -> impl Iterator<Item = &TestRecord> {
+        self.suites.iter().flat_map(|suite| &suite.tests)
+    }
+}
+
+/// File-based storage of run results: each run is stored as a JSON file in the directory.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Stores the run, returning the path to the created file.
+    pub fn save(&self, run: &RunRecord) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.dir.join(format!("{}.json", run.run_id));
+        let json = serde_json::to_string_pretty(run)?;
+        std::fs::write(&path, json)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Loads all the stored runs, ordered from the oldest to the newest.
+    pub fn runs(&self) -> anyhow::Result<Vec<RunRecord>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut runs = Vec::new();
+        let entries = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let run: RunRecord = serde_json::from_str(&json)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            runs.push(run);
+        }
+        runs.sort_by_key(|run| run.started_at_ms);
+        Ok(runs)
+    }
+
+    /// Loads the most recent run, if any.
+    pub fn last_run(&self) -> anyhow::Result<Option<RunRecord>> {
+        Ok(self.runs()?.pop())
+    }
+}
+
+/// Test that both passed and failed across the analyzed runs.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FlakyTest {
+    pub id: TestId,
+    pub passed: usize,
+    pub failed: usize,
+    /// Number of times the status changed between consecutive runs.
+    pub flips: usize,
+}
+
+/// Change of the test duration between the older and the newer half of the runs.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DurationTrend {
+    pub id: TestId,
+    pub before: Duration,
+    pub after: Duration,
+}
+
+impl DurationTrend {
+    /// Relative change of the duration, e.g. `0.5` means that the test became 50% slower.
+    pub fn change(&self) -> f64 {
+        if self.before.is_zero() {
+            return 0.0;
+        }
+        self.after.as_secs_f64() / self.before.as_secs_f64() - 1.0
+    }
+}
+
+/// Analysis of the stored runs.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct HistoryReport {
+    pub runs: usize,
+    pub flaky: Vec<FlakyTest>,
+    pub duration_trends: Vec<DurationTrend>,
+    /// Run the newly failing tests are compared against.
+    pub baseline_run: Option<String>,
+    /// Tests that fail in the latest run, but did not fail in the baseline run.
+    pub newly_failing: Vec<TestId>,
+}
+
+impl HistoryReport {
+    /// Duration changes below this threshold are not reported.
+    const TREND_THRESHOLD: f64 = 0.2;
+
+    /// Analyzes the runs ordered from the oldest to the newest.
+    ///
+    /// Newly failing tests are detected relative to the run with `since` identifier, or
+    /// to the previous run if it's not specified.
+    pub fn analyze(runs: &[RunRecord], since: Option<&str>) -> anyhow::Result<Self> {
+        let mut statuses: BTreeMap<&TestId, Vec<TestStatus>> = BTreeMap::new();
+        let mut durations: BTreeMap<&TestId, Vec<u64>> = BTreeMap::new();
+        for run in runs {
+            for test in run.tests() {
+                statuses.entry(&test.id).or_default().push(test.status);
+                if test.status.executed() {
+                    durations
+                        .entry(&test.id)
+                        .or_default()
+                        .push(test.duration_ms);
+                }
+            }
+        }
+
+        let flaky = statuses
+            .iter()
+            .filter_map(|(id, statuses)| {
+                let executed: Vec<_> = statuses.iter().filter(|status| status.executed()).collect();
+                let passed = executed
+                    .iter()
+                    .filter(|s| ***s == TestStatus::Passed)
+                    .count();
+                let failed = executed.len() - passed;
+                let flips = executed.windows(2).filter(|w| w[0] != w[1]).count();
+                (passed > 0 && failed > 0).then(|| FlakyTest {
+                    id: (*id).clone(),
+                    passed,
+                    failed,
+                    flips,
+                })
+            })
+            .collect();
+
+        let duration_trends = durations
+            .iter()
+            .filter(|(_, durations)| durations.len() >= 2)
+            .filter_map(|(id, durations)| {
+                let (before, after) = durations.split_at(durations.len() / 2);
+                let mean = |values: &[u64]| {
+                    Duration::from_millis(values.iter().sum::<u64>() / values.len() as u64)
+                };
+                let trend = DurationTrend {
+                    id: (*id).clone(),
+                    before: mean(before),
+                    after: mean(after),
+                };
+                (trend.change().abs() >= Self::TREND_THRESHOLD).then_some(trend)
+            })
+            .collect();
+
+        let baseline = match since {
+            Some(run_id) => Some(
+                runs.iter()
+                    .find(|run| run.run_id == run_id)
+                    .with_context(|| format!("Run {run_id} is not found in the history"))?,
+            ),
+            None => runs.len().checked_sub(2).map(|idx| &runs[idx]),
+        };
+        let newly_failing = match (baseline, runs.last()) {
+            (Some(baseline), Some(latest)) => {
+                let failed_before: HashMap<_, _> = baseline
+                    .tests()
+                    .map(|test| (&test.id, test.status == TestStatus::Failed))
+                    .collect();
+                latest
+                    .tests()
+                    .filter(|test| test.status == TestStatus::Failed)
+                    .filter(|test| !failed_before.get(&test.id).copied().unwrap_or(false))
+                    .map(|test| test.id.clone())
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            runs: runs.len(),
+            flaky,
+            duration_trends,
+            baseline_run: baseline.map(|run| run.run_id.clone()),
+            newly_failing,
+        })
+    }
+}
+
+impl fmt::Display for HistoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Analyzed runs: {}", self.runs)?;
+
+        writeln!(f, "Flaky tests:")?;
+        if self.flaky.is_empty() {
+            writeln!(f, "  - None")?;
+        }
+        for test in &self.flaky {
+            writeln!(
+                f,
+                "  - {}: {} passed, {} failed, {} flips",
+                test.id, test.passed, test.failed, test.flips
+            )?;
+        }
+
+        writeln!(f, "Duration trends:")?;
+        if self.duration_trends.is_empty() {
+            writeln!(f, "  - None")?;
+        }
+        for trend in &self.duration_trends {
+            writeln!(
+                f,
+                "  - {}: {:?} -> {:?} ({:+.0}%)",
+                trend.id,
+                trend.before,
+                trend.after,
+                trend.change() * 100.0
+            )?;
+        }
+
+        match &self.baseline_run {
+            Some(run_id) => writeln!(f, "Newly failing since {run_id}:")?,
+            None => writeln!(f, "Newly failing:")?,
+        }
+        if self.newly_failing.is_empty() {
+            writeln!(f, "  - None")?;
+        }
+        for id in &self.newly_failing {
+            writeln!(f, "  - {id}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+/// Identifier of a test suite instance.
+///
+/// The same suite may be instantiated by several constructors, and each of them produces
+/// a separate test suite with its own tests.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TestSuiteId {
+    /// Name of the suite, as provided to the `test_suite` macro.
+    pub suite: String,
+    /// Name of the constructor that created the suite, if it's not the default one.
+    pub constructor_variant: Option<String>,
+}
+
+impl TestSuiteId {
+    pub fn new(suite: impl Into<String>, constructor_variant: Option<String>) -> Self {
+        Self {
+            suite: suite.into(),
+            constructor_variant,
+        }
+    }
+
+    pub fn test(&self, test: impl Into<String>) -> TestId {
+        TestId {
+            suite: self.suite.clone(),
+            constructor_variant: self.constructor_variant.clone(),
+            test: test.into(),
+            param_index: None,
+        }
+    }
+
+    /// Returns the ids of the suite tests with the given names, in declaration order.
+    ///
+    /// Tests sharing a name are told apart by the index of their declaration among them.
+    pub(crate) fn tests(&self, names: &[String]) -> Vec<TestId> {
+        let mut seen = HashMap::<&str, usize>::new();
+        names
+            .iter()
+            .map(|name| {
+                let id = self.test(name);
+                if names.iter().filter(|other| *other == name).count() == 1 {
+                    return id;
+                }
+                let index = seen.entry(name).or_default();
+                *index += 1;
+                id.with_param_index(*index - 1)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for TestSuiteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.constructor_variant {
+            Some(variant) => write!(f, "{} ({})", self.suite, variant),
+            None => write!(f, "{}", self.suite),
+        }
+    }
+}
+
+/// Identifier of a single test within a run.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TestId {
+    /// Name of the suite the test belongs to.
+    pub suite: String,
+    /// Name of the constructor that created the suite, if it's not the default one.
+    pub constructor_variant: Option<String>,
+    /// Name of the test.
+    pub test: String,
+    /// Index of the parameter set for parameterized tests.
+    pub param_index: Option<usize>,
+}
+
+impl TestId {
+    pub fn suite_id(&self) -> TestSuiteId {
+        TestSuiteId::new(self.suite.clone(), self.constructor_variant.clone())
+    }
+
+    pub fn with_param_index(mut self, param_index: usize) -> Self {
+        self.param_index = Some(param_index);
+        self
+    }
+}
+
+impl fmt::Display for TestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} / {}", self.suite_id(), self.test)?;
+        if let Some(idx) = self.param_index {
+            write!(f, " #{}", idx)?;
+        }
+        Ok(())
+    }
+}
@@ -1,32 +1,112 @@
-use std::panic::AssertUnwindSafe;
+use std::{
+    collections::HashSet,
+    ops::RangeInclusive,
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant, SystemTime},
+};
 
 pub use self::{
+    artifacts::{Artifact, attach_bytes, attach_file, attach_text},
     config::TestRunnerConfiguration,
-    reporter::{Reporter, console::ConsoleReporter},
-    traits::{Test, TestSuite, TestSuiteFactory},
+    context::TestContext,
+    eventually::{EventuallyError, eventually},
+    history::{
+        DurationTrend, FlakyTest, HistoryReport, HistoryStore, RunRecord, TestRecord, TestStatus,
+        TestSuiteRecord,
+    },
+    id::{TestId, TestSuiteId},
+    logs::LogCaptureLayer,
+    metadata::{ListFormat, TestMetadata, TestSuiteMetadata},
+    repeat::{RepeatMode, RepeatReport, RepeatStats},
+    reporter::{
+        Reporter,
+        channel::{AsyncReporter, ReporterEvent},
+        console::ConsoleReporter,
+        html::HtmlReporter,
+        tap::TapReporter,
+    },
+    resource::{Resource, ResourceDecl, Resources},
+    scope::{cancellation_token, spawn},
+    shard::Shard,
+    temp_dir::temp_dir,
+    traits::{RunHooks, Test, TestSuite, TestSuiteFactory},
 };
+use anyhow::Context as _;
 /// Procedural macro for defining test suites.
+///
+/// Hooks, fixtures and tests may take `&mut self`, in which case a single suite instance is
+/// shared by all the tests behind a `RwLock`. The lock is held for the whole call of each
+/// method, including the test body, which is dropped to release it if it times out. As the
+/// runner calls the methods of a suite one at a time, `&mut self` methods never wait for it.
 pub use e2e_macro::test_suite;
 use futures::FutureExt;
+/// Token used to cooperatively stop background work of tests.
+pub use tokio_util::sync::CancellationToken;
+use tracing::Instrument as _;
+
+use self::{
+    reporter::Reporters,
+    resource::{ResourceRegistry, ResourceTeardown},
+    scope::Scope,
+    selection::FailedTests,
+    shuffle::Rng,
+};
 
+pub mod process;
+
+mod artifacts;
+mod check;
 mod config;
+mod context;
+mod eventually;
+mod fixture;
+mod history;
+mod id;
+mod logs;
+mod metadata;
+mod repeat;
 mod reporter;
+mod resource;
+mod scope;
+mod selection;
+mod shard;
+mod shuffle;
+mod signal;
+mod temp_dir;
 mod traits;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[non_exhaustive]
 pub struct TestResult {
+    pub id: TestId,
     pub name: String,
     pub ignored: bool,
     pub error: Option<TestError>,
+    pub duration: Duration,
+    /// Logs captured by [`LogCaptureLayer`] while the test was running.
+    pub logs: Vec<String>,
+    /// 1-based iteration number when tests are repeated.
+    pub iteration: usize,
+    /// Artifacts attached while the test was running.
+    pub artifacts: Vec<Artifact>,
+    /// Temporary directory of the test, if it was kept after the test finished.
+    pub temp_dir: Option<PathBuf>,
 }
 
 impl TestResult {
-    pub fn new(name: String) -> Self {
+    pub fn new(id: TestId) -> Self {
         Self {
-            name,
+            name: id.test.clone(),
+            id,
             ignored: false,
             error: None,
+            duration: Duration::ZERO,
+            logs: Vec::new(),
+            iteration: 1,
+            artifacts: Vec::new(),
+            temp_dir: None,
         }
     }
 
@@ -34,6 +114,11 @@ impl TestResult {
         self.error.is_none()
     }
 
+    /// Whether the test was cancelled because the run was interrupted.
+    pub fn cancelled(&self) -> bool {
+        matches!(self.error, Some(TestError::Cancelled(_)))
+    }
+
     pub fn set_ignored(&mut self, ignored: bool) {
         self.ignored = ignored;
     }
@@ -41,24 +126,54 @@ impl TestResult {
     pub fn set_error(&mut self, error: TestError) {
         self.error = Some(error);
     }
+
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    pub fn set_logs(&mut self, logs: Vec<String>) {
+        self.logs = logs;
+    }
+
+    pub fn set_iteration(&mut self, iteration: usize) {
+        self.iteration = iteration;
+    }
+
+    pub fn set_artifacts(&mut self, artifacts: Vec<Artifact>) {
+        self.artifacts = artifacts;
+    }
+
+    pub fn set_temp_dir(&mut self, temp_dir: Option<PathBuf>) {
+        self.temp_dir = temp_dir;
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub struct TestSuiteResult {
+    pub id: TestSuiteId,
     pub name: String,
     pub passed: bool,
     pub tests: Vec<TestResult>,
     pub error: Option<TestError>,
+    pub duration: Duration,
+    /// Artifacts attached by the constructor and suite-level hooks.
+    pub artifacts: Vec<Artifact>,
+    /// Temporary directory of the suite, if it was kept after the suite finished.
+    pub temp_dir: Option<PathBuf>,
 }
 
 impl TestSuiteResult {
-    pub fn new(name: String) -> Self {
+    pub fn new(id: TestSuiteId) -> Self {
         Self {
-            name,
+            name: id.to_string(),
+            id,
             passed: true,
             tests: Vec::new(),
             error: None,
+            duration: Duration::ZERO,
+            artifacts: Vec::new(),
+            temp_dir: None,
         }
     }
 
@@ -73,6 +188,18 @@ impl TestSuiteResult {
         self.error = Some(error);
         self.passed = false;
     }
+
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    pub fn set_artifacts(&mut self, artifacts: Vec<Artifact>) {
+        self.artifacts = artifacts;
+    }
+
+    pub fn set_temp_dir(&mut self, temp_dir: Option<PathBuf>) {
+        self.temp_dir = temp_dir;
+    }
 }
 
 pub fn init() {
@@ -84,27 +211,56 @@ pub fn init() {
 }
 
 #[derive(Debug)]
-pub struct TestRunner<C: std::fmt::Debug + 'static> {
+pub struct TestRunner<C: std::fmt::Debug + Sync + 'static> {
     /// Configuration for the test suites.
     config: C,
     /// Configuration for the test runner.
     runner_config: TestRunnerConfiguration,
     /// List of test suites to run.
     test_suites: Vec<Box<dyn TestSuiteFactory<C>>>,
-    /// Reporter for test events.
-    reporter: Box<dyn Reporter>,
+    /// Reporters for test events.
+    reporters: Reporters,
+    /// Hooks invoked before and after all the test suites.
+    run_hooks: Vec<Box<dyn RunHooks<C>>>,
     /// Results of test runs
     results: Vec<TestSuiteResult>,
+    /// Tests that failed in the previous run, if only they must be run.
+    failed_tests: Option<FailedTests>,
+    /// Seed used to shuffle suites and tests, if shuffling is enabled.
+    seed: Option<u64>,
+    /// Token cancelled when the run is interrupted.
+    cancellation: CancellationToken,
+    /// Deadline for teardown hooks, set once the run is interrupted.
+    teardown_deadline: OnceLock<Instant>,
+    /// Directory to store artifacts of the current run in.
+    run_artifacts_dir: PathBuf,
+    /// Directory to create temporary directories of the current run in.
+    run_temp_dir: PathBuf,
+    /// Resources shared by the suites of the run.
+    resources: ResourceRegistry<C>,
+    /// Scope of the run hooks and the shared resources, which is shut down once the run
+    /// finishes.
+    run_scope: Scope,
 }
 
-impl<C: std::fmt::Debug + 'static> TestRunner<C> {
+impl<C: std::fmt::Debug + Sync + 'static> TestRunner<C> {
     pub fn new(config: C) -> Self {
+        let cancellation = CancellationToken::new();
         Self {
             config,
             runner_config: Default::default(),
             test_suites: Vec::new(),
-            reporter: Box::new(ConsoleReporter::new()),
+            reporters: Reporters::new(vec![Box::new(ConsoleReporter::new())]),
+            run_hooks: Vec::new(),
             results: Vec::new(),
+            failed_tests: None,
+            seed: None,
+            run_scope: Scope::nested(&cancellation, PathBuf::new(), PathBuf::new()),
+            cancellation,
+            teardown_deadline: OnceLock::new(),
+            run_artifacts_dir: PathBuf::new(),
+            run_temp_dir: PathBuf::new(),
+            resources: ResourceRegistry::new(),
         }
     }
 
@@ -113,125 +269,698 @@ impl<C: std::fmt::Debug + 'static> TestRunner<C> {
         self
     }
 
+    /// Replaces the default reporters with the provided one.
+    pub fn with_reporter(mut self, reporter: Box<dyn Reporter>) -> Self {
+        self.reporters = Reporters::new(vec![reporter]);
+        self
+    }
+
+    pub fn add_reporter(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporters.add(reporter);
+    }
+
+    /// Adds a reporter that will process events on its own task.
+    pub fn add_async_reporter(&mut self, reporter: Box<dyn AsyncReporter>) {
+        self.reporters.add_async(reporter);
+    }
+
+    /// Adds hooks that are invoked once before and after all the test suites.
+    ///
+    /// `before_all` hooks are invoked in the order they were added, and `after_all`
+    /// hooks in the reverse order.
+    pub fn add_run_hooks(&mut self, hooks: Box<dyn RunHooks<C>>) {
+        self.run_hooks.push(hooks);
+    }
+
     pub fn add_suite(&mut self, factory: Box<dyn TestSuiteFactory<C>>) {
         self.test_suites.push(factory);
     }
 
+    /// Returns the token that interrupts the run when cancelled.
+    ///
+    /// The token is cancelled on SIGINT or SIGTERM. Once cancelled, the running test is
+    /// stopped, teardown hooks are given the configured grace period to finish, and the
+    /// remaining tests are reported as cancelled.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
     pub async fn run(mut self) -> anyhow::Result<()> {
-        for factory in &std::mem::take(&mut self.test_suites) {
-            let name = factory.name();
-            if self
-                .runner_config
-                .test_suite_filter
-                .as_ref()
-                .is_some_and(|filter| !filter.is_match(&name))
+        if self.runner_config.history_report {
+            return self.print_history_report();
+        }
+        if self.runner_config.list {
+            let suites = self.list()?;
+            println!(
+                "{}",
+                metadata::render_list(&suites, self.runner_config.list_format)
+            );
+            return Ok(());
+        }
+
+        if self.runner_config.rerun_failed {
+            self.failed_tests = Some(self.load_failed_tests()?);
+        }
+
+        let shard_suites = self.shard_suites()?;
+        self.check_repeat(shard_suites.as_ref())?;
+
+        let mut test_suites = std::mem::take(&mut self.test_suites);
+        if self.runner_config.shuffle {
+            let seed = self.runner_config.seed.unwrap_or_else(Rng::random_seed);
+            println!(
+                "Shuffling suites and tests with seed {seed} (use `--seed {seed}` to reproduce)"
+            );
+            Rng::new(seed).shuffle(&mut test_suites);
+            self.seed = Some(seed);
+        }
+
+        let run_started_at = SystemTime::now();
+        let run_id = history::run_id(run_started_at);
+        self.run_artifacts_dir = self.runner_config.artifacts_dir().join(&run_id);
+        self.run_temp_dir =
+            std::env::temp_dir().join(format!("e2e-{run_id}-{}", std::process::id()));
+        self.reporters.start();
+        let _signals = signal::handle_signals(self.cancellation.clone());
+
+        self.run_scope = self.scope(PathBuf::new(), true);
+        let run_scope = self.run_scope.clone();
+        let mut run_errors = run_scope
+            .run(self.run_with_hooks(&test_suites, shard_suites.as_ref()))
+            .await;
+        let aborted = run_scope
+            .shutdown(self.runner_config.spawned_tasks_timeout())
+            .await;
+        if aborted > 0 {
+            tracing::warn!(
+                "Aborted {aborted} unfinished task(s) spawned by run hooks or resources"
+            );
+        }
+        if self.cancellation.is_cancelled() {
+            run_errors.insert(0, anyhow::format_err!("Run was cancelled"));
+        }
+
+        self.reporters.on_run_finished(&self.results);
+        self.reporters.finish().await;
+        temp_dir::remove_empty_dirs(&self.run_temp_dir);
+
+        if self.runner_config.is_repeated() {
+            println!("{}", RepeatReport::new(&self.results));
+        }
+
+        if let Some(dir) = &self.runner_config.history_dir {
+            let run = RunRecord::new(run_started_at, &self.results);
+            let path = HistoryStore::new(dir).save(&run)?;
+            tracing::info!("Run results are stored in {}", path.display());
+        }
+
+        match run_errors.len() {
+            0 => Ok(()),
+            1 => Err(run_errors.remove(0)),
+            _ => {
+                for err in &run_errors {
+                    tracing::error!("{:?}", err);
+                }
+                Err(run_errors.remove(0))
+            }
+        }
+    }
+
+    /// Returns metadata of suites and tests selected by filters, without creating suites.
+    pub fn list(&self) -> anyhow::Result<Vec<TestSuiteMetadata>> {
+        let shard_suites = self.shard_suites()?;
+        let suites = self
+            .test_suites
+            .iter()
+            .map(|factory| TestSuiteMetadata::new(factory.id(), factory.tests_metadata()))
+            .filter(|suite| {
+                shard_suites
+                    .as_ref()
+                    .is_none_or(|suites| suites.contains(&suite.id))
+            })
+            .filter(|suite| {
+                self.runner_config
+                    .test_suite_filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.is_match(&suite.name))
+            })
+            .map(|mut suite| {
+                suite.tests.retain(|test| {
+                    self.runner_config
+                        .test_case_filter
+                        .as_ref()
+                        .is_none_or(|filter| filter.is_match(&test.name))
+                });
+                suite
+            })
+            .collect();
+        Ok(suites)
+    }
+
+    /// Returns suites assigned to the configured shard, if any.
+    fn shard_suites(&self) -> anyhow::Result<Option<HashSet<TestSuiteId>>> {
+        let Some(shard) = self.runner_config.shard else {
+            return Ok(None);
+        };
+        let durations = self
+            .runner_config
+            .shard_durations
+            .as_deref()
+            .map(shard::load_durations)
+            .transpose()?;
+        let ids: Vec<_> = self
+            .test_suites
+            .iter()
+            .map(|factory| factory.id())
+            .collect();
+        Ok(Some(shard.select(&ids, durations.as_ref())))
+    }
+
+    /// Validates the repeat options against the selected suites.
+    ///
+    /// Without `--repeat`, `--until-failure` repeats a suite indefinitely, so any suite
+    /// after it would never run.
+    fn check_repeat(&self, shard_suites: Option<&HashSet<TestSuiteId>>) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.runner_config.repeat != Some(0),
+            "`--repeat` must be at least 1"
+        );
+        if self.runner_config.until_failure && self.runner_config.repeat.is_none() {
+            let selected = self
+                .test_suites
+                .iter()
+                .filter(|factory| self.is_suite_selected(&factory.id(), shard_suites))
+                .count();
+            anyhow::ensure!(
+                selected <= 1,
+                "`--until-failure` without `--repeat` requires selecting a single test suite \
+                 (e.g. with `--test-suite-filter`), but {selected} are selected"
+            );
+        }
+        Ok(())
+    }
+
+    fn load_failed_tests(&self) -> anyhow::Result<FailedTests> {
+        let dir = self
+            .runner_config
+            .history_dir
+            .as_ref()
+            .context("History directory is not configured")?;
+        let last_run = HistoryStore::new(dir)
+            .last_run()?
+            .with_context(|| format!("No previous runs found in {}", dir.display()))?;
+        let failed_tests = FailedTests::from_run(&last_run);
+        if failed_tests.is_empty() {
+            tracing::info!("No tests failed in {}", last_run.run_id);
+        }
+        Ok(failed_tests)
+    }
+
+    fn print_history_report(&self) -> anyhow::Result<()> {
+        let dir = self
+            .runner_config
+            .history_dir
+            .as_ref()
+            .context("History directory is not configured")?;
+        let runs = HistoryStore::new(dir).runs()?;
+        let report = HistoryReport::analyze(&runs, self.runner_config.history_since.as_deref())?;
+        println!("{report}");
+        Ok(())
+    }
+
+    /// Runs the suites wrapped into the run hooks, returning the errors of the run.
+    async fn run_with_hooks(
+        &mut self,
+        test_suites: &[Box<dyn TestSuiteFactory<C>>],
+        shard_suites: Option<&HashSet<TestSuiteId>>,
+    ) -> Vec<anyhow::Error> {
+        // `after_all` is only invoked for hooks which `before_all` was invoked.
+        let mut hooks_started = 0;
+        let mut run_errors = Vec::new();
+        for hooks in &self.run_hooks {
+            if self.cancellation.is_cancelled() {
+                break;
+            }
+            hooks_started += 1;
+            if let Err(err) = check::run(hooks.before_all(&self.config)).await {
+                run_errors.push(err.context("Run 'before_all' hook failed"));
+                break;
+            }
+        }
+        if run_errors.is_empty() {
+            let errors = self.run_suites(test_suites, shard_suites).await;
+            run_errors.extend(errors);
+        }
+        for hooks in self.run_hooks[..hooks_started].iter().rev() {
+            if let Err(err) = self
+                .teardown(check::run(hooks.after_all(&self.config)))
+                .await
             {
-                self.reporter.on_test_suite_ignored(&name);
+                run_errors.push(err.context("Run 'after_all' hook failed"));
+            }
+        }
+        run_errors
+    }
+
+    /// Runs the suites, returning errors of resource teardowns.
+    ///
+    /// Resources are torn down right after the last suite that declares them, so all the
+    /// suites that will be run are registered as resource users before running any.
+    async fn run_suites(
+        &mut self,
+        test_suites: &[Box<dyn TestSuiteFactory<C>>],
+        shard_suites: Option<&HashSet<TestSuiteId>>,
+    ) -> Vec<anyhow::Error> {
+        for factory in test_suites {
+            if self.is_suite_selected(&factory.id(), shard_suites) {
+                self.resources.add_user(factory.resources());
+            }
+        }
+
+        let mut errors = Vec::new();
+        for factory in test_suites {
+            let id = factory.id();
+            if shard_suites.is_some_and(|suites| !suites.contains(&id)) {
+                // Suite is executed by another shard.
                 continue;
             }
+            if !self.is_suite_selected(&id, shard_suites) {
+                self.reporters.on_test_suite_ignored(&id);
+                continue;
+            }
+
+            let mut stop = false;
+            if self.cancellation.is_cancelled() {
+                let result = self.cancelled_suite_result(&**factory, id);
+                self.results.push(result);
+            } else {
+                let passed = self.run_factory(&**factory, id).await;
+                stop = !passed && self.should_stop() && !self.cancellation.is_cancelled();
+            }
+            let teardowns = self.resources.release(&factory.resources());
+            errors.extend(self.tear_down_resources(teardowns).await);
+            if stop {
+                break;
+            }
+        }
+        // Resources of the suites skipped after a failure.
+        let teardowns = self.resources.release_all();
+        errors.extend(self.tear_down_resources(teardowns).await);
+        errors
+    }
 
-            let mut result = TestSuiteResult::new(name.clone());
+    /// Whether the suite is assigned to this shard and is not excluded by filters.
+    fn is_suite_selected(
+        &self,
+        id: &TestSuiteId,
+        shard_suites: Option<&HashSet<TestSuiteId>>,
+    ) -> bool {
+        if shard_suites.is_some_and(|suites| !suites.contains(id)) {
+            return false;
+        }
+        let mut ignore = self
+            .runner_config
+            .test_suite_filter
+            .as_ref()
+            .is_some_and(|filter| !filter.is_match(&id.to_string()));
+        ignore |= self
+            .failed_tests
+            .as_ref()
+            .is_some_and(|failed| !failed.includes_suite(id));
+        !ignore
+    }
 
-            self.reporter.on_test_suite_creation_started(&name);
-            let suite_result = factory
-                .create_suite(&self.config)
-                .await
-                .map_err(TestError::CreateSuite);
-            self.reporter
-                .on_test_suite_creation_finished(&name, suite_result.as_ref().err());
-            self.reporter.on_test_suite_start(&name);
-            match suite_result {
-                Ok(suite) => {
-                    self.run_suite(suite, &mut result).await;
-                }
-                Err(err) => {
-                    result.set_error(err);
-                }
+    /// Tears down resources that are no longer used, returning the errors.
+    async fn tear_down_resources(&self, teardowns: Vec<ResourceTeardown>) -> Vec<anyhow::Error> {
+        let mut errors = Vec::new();
+        for teardown in teardowns {
+            if let Err(err) = self.teardown(teardown.run()).await {
+                errors.push(err);
             }
-            self.reporter.on_test_suite_end(&name, &result);
+        }
+        errors
+    }
 
+    /// Runs all the iterations of the suite, returning whether all of them passed.
+    async fn run_factory(&mut self, factory: &dyn TestSuiteFactory<C>, id: TestSuiteId) -> bool {
+        let iterations = self.runner_config.iterations();
+        // Iterations are generated lazily, since `--until-failure` may repeat indefinitely.
+        let runs: Box<dyn Iterator<Item = RangeInclusive<usize>> + Send> =
+            match self.runner_config.repeat_mode {
+                RepeatMode::Fresh => Box::new((1..=iterations).map(|i| i..=i)),
+                RepeatMode::Reuse => Box::new(std::iter::once(1..=iterations)),
+            };
+        let mut all_passed = true;
+        for iterations in runs {
+            if self.cancellation.is_cancelled() {
+                break;
+            }
+            let result = self
+                .run_suite_instance(factory, id.clone(), iterations)
+                .await;
             let passed = result.passed;
             self.results.push(result);
-
-            if !passed && self.runner_config.fail_fast {
+            all_passed &= passed;
+            if !passed && self.should_stop() {
                 break;
             }
         }
+        all_passed
+    }
 
-        Ok(())
+    /// Creates the suite and runs the specified iterations of its tests.
+    async fn run_suite_instance(
+        &mut self,
+        factory: &dyn TestSuiteFactory<C>,
+        id: TestSuiteId,
+        iterations: RangeInclusive<usize>,
+    ) -> TestSuiteResult {
+        let scope = self.scope(
+            artifacts::relative_path(&[&id.to_string()], *iterations.start()),
+            true,
+        );
+        let mut result = scope
+            .run(self.create_and_run_suite(factory, id.clone(), iterations))
+            .await;
+        let aborted = scope
+            .shutdown(self.runner_config.spawned_tasks_timeout())
+            .await;
+        if aborted > 0 {
+            tracing::warn!("Aborted {aborted} unfinished task(s) spawned by suite {id}");
+        }
+        // Tasks spawned by the suite may fail soft assertions until they are shut down.
+        if let Some(err) = TestError::merge_failed_checks(
+            result.error.take(),
+            scope.take_failed_checks(),
+            TestError::AfterAll,
+        ) {
+            result.set_error(err);
+        }
+        result.set_artifacts(scope.artifacts().take());
+        result.set_temp_dir(self.finish_temp_dir(&scope, &id, result.passed));
+        self.reporters.on_test_suite_end(&id, &result);
+        result
+    }
+
+    async fn create_and_run_suite(
+        &mut self,
+        factory: &dyn TestSuiteFactory<C>,
+        id: TestSuiteId,
+        iterations: RangeInclusive<usize>,
+    ) -> TestSuiteResult {
+        let mut result = TestSuiteResult::new(id.clone());
+        let started_at = Instant::now();
+
+        self.reporters.on_test_suite_creation_started(&id);
+        // Resources outlive the suite, so they belong to the run scope.
+        let resources = factory.resources();
+        let acquired = self
+            .run_scope
+            .run(self.resources.acquire(&resources, &self.config))
+            .await;
+        let suite_result = match acquired {
+            Ok(resources) => check::run(factory.create_suite(&self.config, &resources)).await,
+            Err(err) => Err(err),
+        }
+        .map_err(|err| TestError::CreateSuite(err.into()));
+        self.reporters
+            .on_test_suite_creation_finished(&id, suite_result.as_ref().err());
+        self.reporters.on_test_suite_start(&id);
+        match suite_result {
+            Ok(suite) => {
+                self.run_suite(suite, &mut result, iterations).await;
+            }
+            Err(err) => {
+                result.set_error(err);
+            }
+        }
+        result.set_duration(started_at.elapsed());
+        result
+    }
+
+    /// Builds the result of a suite that was not run because the run was interrupted.
+    ///
+    /// Reporters receive the same sequence of events as for a suite that was run.
+    fn cancelled_suite_result(
+        &mut self,
+        factory: &dyn TestSuiteFactory<C>,
+        id: TestSuiteId,
+    ) -> TestSuiteResult {
+        let mut result = TestSuiteResult::new(id.clone());
+        self.reporters.on_test_suite_creation_started(&id);
+        self.reporters.on_test_suite_creation_finished(&id, None);
+        self.reporters.on_test_suite_start(&id);
+        let tests = factory.tests_metadata();
+        let has_only = tests.iter().any(|test| test.only);
+        let names: Vec<_> = tests.iter().map(|test| test.name.clone()).collect();
+        for (test, test_id) in tests.into_iter().zip(id.tests(&names)) {
+            if !self.is_test_ignored(&test_id, test.ignore, test.only, has_only) {
+                result.add_test_result(self.cancelled_test_result(test_id));
+            }
+        }
+        self.reporters.on_test_suite_end(&id, &result);
+        result
+    }
+
+    /// Builds the result of a test that was not run because the run was interrupted.
+    fn cancelled_test_result(&mut self, id: TestId) -> TestResult {
+        let mut result = TestResult::new(id.clone());
+        result.set_error(TestError::cancelled());
+        self.reporters.on_test_start(&id);
+        self.reporters.on_test_end(&id, result.error.as_ref());
+        result
+    }
+
+    /// Whether the test is excluded from the run by its attributes or the configuration.
+    fn is_test_ignored(&self, id: &TestId, ignore: bool, only: bool, has_only: bool) -> bool {
+        let mut ignored = ignore && !self.runner_config.run_ignored;
+        ignored |= has_only && !only;
+        ignored |= self
+            .runner_config
+            .test_case_filter
+            .as_ref()
+            .is_some_and(|filter| !filter.is_match(&id.test));
+        ignored |= self
+            .failed_tests
+            .as_ref()
+            .is_some_and(|failed| !failed.includes_test(id));
+        ignored
+    }
+
+    /// Runs a teardown hook, bounding it by the grace period if the run was interrupted.
+    async fn teardown(&self, hook: impl Future<Output = anyhow::Result<()>>) -> anyhow::Result<()> {
+        if !self.cancellation.is_cancelled() {
+            return hook.await;
+        }
+        let deadline = *self
+            .teardown_deadline
+            .get_or_init(|| Instant::now() + self.runner_config.cancellation_grace_period());
+        tokio::time::timeout_at(deadline.into(), hook)
+            .await
+            .unwrap_or_else(|_| {
+                Err(anyhow::format_err!(
+                    "Teardown did not finish within the cancellation grace period"
+                ))
+            })
+    }
+
+    /// Creates a scope for a suite or a test, nested into the current one.
+    ///
+    /// Artifacts of a scope that contains other ones are stored in their own subdirectory.
+    fn scope(&self, relative_path: PathBuf, has_children: bool) -> Scope {
+        let mut artifacts_dir = self.run_artifacts_dir.join(&relative_path);
+        if has_children {
+            artifacts_dir.push(artifacts::OWN_ARTIFACTS_DIR);
+        }
+        Scope::nested(
+            &self.cancellation,
+            artifacts_dir,
+            self.run_temp_dir.join(&relative_path),
+        )
+    }
+
+    /// Removes the temporary directory of the scope unless it must be kept.
+    fn finish_temp_dir(
+        &self,
+        scope: &Scope,
+        owner: &dyn std::fmt::Display,
+        passed: bool,
+    ) -> Option<PathBuf> {
+        let kept = scope
+            .temp_dir()
+            .finish(!passed || self.runner_config.keep_temp);
+        if let Some(dir) = &kept {
+            tracing::info!("Keeping temporary directory of {owner}: {}", dir.display());
+        }
+        kept
+    }
+
+    /// Whether the run must be stopped after a failure.
+    fn should_stop(&self) -> bool {
+        self.runner_config.fail_fast || self.runner_config.until_failure
     }
 
     async fn run_test(
         &mut self,
         suite: &dyn TestSuite,
         test: &dyn Test,
+        id: TestId,
+        iteration: usize,
         ignore: bool,
     ) -> TestResult {
-        let mut test_result = TestResult::new(test.name());
-
         if ignore {
+            let mut test_result = TestResult::new(id.clone());
             test_result.set_ignored(true);
-            self.reporter.on_test_ignored(&test.name());
+            self.reporters.on_test_ignored(&id);
             return test_result;
         }
 
-        if let Err(err) = suite.before_each().await.map_err(TestError::BeforeEach) {
+        // Tests sharing a name must not share their artifacts.
+        let test_name = match id.param_index {
+            Some(index) => format!("{} #{index}", id.test),
+            None => id.test.clone(),
+        };
+        let scope = self.scope(
+            artifacts::relative_path(&[&id.suite_id().to_string(), &test_name], iteration),
+            false,
+        );
+        let ctx = TestContext::new(id.clone(), iteration, &scope);
+        let execution = self
+            .execute_test(suite, test, &ctx)
+            .instrument(ctx.span().clone());
+        let (mut test_result, logs) = logs::capture(scope.run(execution)).await;
+        test_result.set_logs(logs);
+        let aborted = scope
+            .shutdown(self.runner_config.spawned_tasks_timeout())
+            .await;
+        if aborted > 0 {
+            tracing::warn!("Aborted {aborted} unfinished task(s) spawned by test {id}");
+        }
+        // Tasks spawned by the test may fail soft assertions until they are shut down.
+        test_result.error = TestError::merge_failed_checks(
+            test_result.error.take(),
+            scope.take_failed_checks(),
+            TestError::Test,
+        );
+        test_result.set_artifacts(scope.artifacts().take());
+        test_result.set_temp_dir(self.finish_temp_dir(&scope, &id, test_result.passed()));
+        test_result
+    }
+
+    /// Fails the test if its body doesn't finish by the deadline, still giving its fixtures
+    /// the teardown timeout to be torn down, from the time the body finishes or times out.
+    async fn enforce_deadline(
+        &self,
+        ctx: &TestContext,
+        test_future: impl Future<Output = anyhow::Result<()>>,
+    ) -> anyhow::Result<()> {
+        let (Some(deadline), Some(timeout)) = (ctx.deadline(), ctx.timeout()) else {
+            return test_future.await;
+        };
+        let teardown_timeout = self.runner_config.teardown_timeout();
+        let mut test_future = std::pin::pin!(test_future);
+        let body_finished = tokio::select! {
+            biased;
+            res = test_future.as_mut() => return res,
+            _ = ctx.body_finished() => true,
+            _ = tokio::time::sleep_until(deadline.into()) => false,
+        };
+        let res = tokio::time::timeout(teardown_timeout, test_future).await;
+        if !body_finished {
+            return Err(fixture::timeout_error(timeout));
+        }
+        res.unwrap_or_else(|_| Err(fixture::teardown_timeout_error(teardown_timeout)))
+    }
+
+    /// Runs the test along with `before_each` and `after_each` hooks.
+    async fn execute_test(
+        &mut self,
+        suite: &dyn TestSuite,
+        test: &dyn Test,
+        ctx: &TestContext,
+    ) -> TestResult {
+        let id = ctx.id().clone();
+        let mut test_result = TestResult::new(id.clone());
+
+        if let Err(err) = check::run(suite.before_each())
+            .await
+            .map_err(|err| TestError::BeforeEach(err.into()))
+        {
             test_result.set_error(err);
             return test_result;
         }
 
-        self.reporter.on_test_start(&test.name());
+        self.reporters.on_test_start(&id);
+        let started_at = Instant::now();
 
         // Handle panics in gests
-        let panic_handling_future =
-            AssertUnwindSafe(test.run())
-                .catch_unwind()
-                .map(|res| match res {
-                    Ok(res) => res,
-                    Err(panic_err) => {
-                        let err = if let Some(err) = panic_err.downcast_ref::<String>() {
-                            anyhow::format_err!("Test panicked with message: {}", err)
-                        } else if let Some(err) = panic_err.downcast_ref::<&str>() {
-                            anyhow::format_err!("Test panicked with message: {}", err)
-                        } else {
-                            anyhow::format_err!("Test panicked with an unknown error type")
-                        };
-                        Err(err)
-                    }
-                });
-
-        let test_future = tokio::time::timeout(self.runner_config.timeout(), panic_handling_future)
-            .map(|res| match res {
-                Ok(res) => res,
-                Err(_) => Err(anyhow::format_err!(
-                    "Test timed out after {:?}",
-                    self.runner_config.timeout()
-                )),
-            });
+        let panic_handling_future = AssertUnwindSafe(test.run(ctx))
+            .catch_unwind()
+            .map(|res| res.unwrap_or_else(|payload| Err(fixture::panic_error(payload))));
 
-        let test_run_result = test_future.await.map_err(TestError::Test);
+        let timeout = self.runner_config.timeout();
+        ctx.start_timer(timeout);
+        let test_run_result = {
+            let mut test_future = std::pin::pin!(self.enforce_deadline(ctx, panic_handling_future));
+            // The body is stopped on cancellation too, which must not be reported as a failure.
+            let res = tokio::select! {
+                biased;
+                _ = self.cancellation.cancelled() => None,
+                res = test_future.as_mut() => Some(res),
+            };
+            if res.is_none() {
+                // Keep polling the interrupted test, so that its fixtures are torn down.
+                // Its outcome is superseded by the cancellation.
+                if let Err(err) = self.teardown(test_future.map(|_| Ok(()))).await {
+                    tracing::warn!("Fixtures of test {id} were not torn down: {err:#}");
+                }
+            }
+            res
+        };
+        // Failed soft assertions fail the test once it finishes, even if it timed out.
+        let failed_checks = check::take_failed();
+        let test_run_result = match test_run_result {
+            Some(res) => {
+                check::merge(res, failed_checks).map_err(|err| TestError::Test(err.into()))
+            }
+            None => Err(TestError::cancelled()),
+        };
+        test_result.set_duration(started_at.elapsed());
 
-        self.reporter
-            .on_test_end(&test.name(), test_run_result.as_ref().err());
+        self.reporters
+            .on_test_end(&id, test_run_result.as_ref().err());
 
         if let Err(err) = test_run_result {
+            let cancelled = matches!(err, TestError::Cancelled(_));
             test_result.set_error(err);
-            // Do not run `after_each` if failing fast.
-            if self.runner_config.fail_fast {
+            // Do not run `after_each` if failing fast, unless the test was interrupted.
+            if self.runner_config.fail_fast && !cancelled {
                 return test_result;
             }
         }
 
         // TODO: do not override test error
-        if let Err(err) = suite.after_each().await.map_err(TestError::AfterEach) {
+        if let Err(err) = self
+            .teardown(check::run(suite.after_each()))
+            .await
+            .map_err(|err| TestError::AfterEach(err.into()))
+        {
             test_result.set_error(err);
         }
 
         test_result
     }
 
-    async fn run_suite(&mut self, suite: Box<dyn TestSuite>, result: &mut TestSuiteResult) {
-        if let Err(err) = suite.before_all().await.map_err(TestError::BeforeAll) {
+    async fn run_suite(
+        &mut self,
+        suite: Box<dyn TestSuite>,
+        result: &mut TestSuiteResult,
+        iterations: RangeInclusive<usize>,
+    ) {
+        if let Err(err) = check::run(suite.before_all())
+            .await
+            .map_err(|err| TestError::BeforeAll(err.into()))
+        {
             result.set_error(err);
             return;
         }
@@ -239,47 +968,148 @@ impl<C: std::fmt::Debug + 'static> TestRunner<C> {
         // Check if at least one test has `only` set to true.
         let has_only = suite.tests().iter().any(|test| test.only());
 
-        for test in suite.tests() {
-            let mut ignore = test.ignore() && !self.runner_config.run_ignored;
-            ignore |= has_only && !test.only();
-            ignore |= self
-                .runner_config
-                .test_case_filter
-                .as_ref()
-                .is_some_and(|filter| !filter.is_match(&test.name()));
-
-            let test_result = self.run_test(&*suite, &*test, ignore).await;
-            let test_passed = test_result.passed();
-            result.add_test_result(test_result);
-            if !test_passed && self.runner_config.fail_fast {
-                return;
+        let mut rng = self.seed.map(|seed| Rng::scoped(seed, &result.name));
+        'iterations: for iteration in iterations {
+            let tests = suite.tests();
+            let names: Vec<_> = tests.iter().map(|test| test.name()).collect();
+            let mut tests: Vec<_> = tests.into_iter().zip(result.id.tests(&names)).collect();
+            if let Some(rng) = &mut rng {
+                rng.shuffle(&mut tests);
+            }
+            for (test, test_id) in tests {
+                let ignore = self.is_test_ignored(&test_id, test.ignore(), test.only(), has_only);
+                if self.cancellation.is_cancelled() {
+                    if !ignore {
+                        let mut test_result = self.cancelled_test_result(test_id);
+                        test_result.set_iteration(iteration);
+                        result.add_test_result(test_result);
+                    }
+                    continue;
+                }
+
+                let mut test_result = self
+                    .run_test(&*suite, &*test, test_id, iteration, ignore)
+                    .await;
+                test_result.set_iteration(iteration);
+                let test_passed = test_result.passed();
+                let test_cancelled = test_result.cancelled();
+                result.add_test_result(test_result);
+                if !test_passed && self.runner_config.fail_fast && !test_cancelled {
+                    return;
+                }
+                if !test_passed && self.runner_config.until_failure && !test_cancelled {
+                    break 'iterations;
+                }
+            }
+            if self.cancellation.is_cancelled() {
+                break;
             }
         }
 
-        if let Err(err) = suite.after_all().await.map_err(TestError::AfterAll) {
+        if let Err(err) = self
+            .teardown(check::run(suite.after_all()))
+            .await
+            .map_err(|err| TestError::AfterAll(err.into()))
+        {
             result.set_error(err);
         }
     }
 }
 
-#[derive(Debug, thiserror::Error)]
+/// Error that occurred during the run.
+///
+/// Underlying errors are reference-counted, so that errors can be cheaply cloned and sent
+/// to reporters that don't run on the runner's task.
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum TestError {
     #[error("Failed to create test suite: {0:?}")]
-    CreateSuite(anyhow::Error),
+    CreateSuite(Arc<anyhow::Error>),
     #[error("Failed to run 'before_all' for the test suite: {0:?}")]
-    BeforeAll(anyhow::Error),
+    BeforeAll(Arc<anyhow::Error>),
     #[error("Failed to run 'before_each' the test suite: {0:?}")]
-    BeforeEach(anyhow::Error),
+    BeforeEach(Arc<anyhow::Error>),
     #[error("Failed to run 'after_each' the test: {0:?}")]
-    AfterEach(anyhow::Error),
+    AfterEach(Arc<anyhow::Error>),
     #[error("Failed to run 'after_all' the test: {0:?}")]
-    AfterAll(anyhow::Error),
+    AfterAll(Arc<anyhow::Error>),
     #[error("Test failed: {0:?}")]
-    Test(anyhow::Error),
+    Test(Arc<anyhow::Error>),
+    #[error("Test was cancelled: {0:?}")]
+    Cancelled(Arc<anyhow::Error>),
+}
+
+impl TestError {
+    /// Returns the phase of the run in which the error occurred.
+    pub fn phase(&self) -> &'static str {
+        match self {
+            Self::CreateSuite(_) => "create_suite",
+            Self::BeforeAll(_) => "before_all",
+            Self::BeforeEach(_) => "before_each",
+            Self::AfterEach(_) => "after_each",
+            Self::AfterAll(_) => "after_all",
+            Self::Test(_) => "test",
+            Self::Cancelled(_) => "cancelled",
+        }
+    }
+
+    pub(crate) fn cancelled() -> Self {
+        Self::Cancelled(Arc::new(anyhow::format_err!("Run was interrupted")))
+    }
+
+    /// Fails with the soft assertions that failed after the phase finished, keeping the
+    /// original error and its phase if there is one.
+    pub(crate) fn merge_failed_checks(
+        error: Option<Self>,
+        failed_checks: Vec<String>,
+        phase: fn(Arc<anyhow::Error>) -> Self,
+    ) -> Option<Self> {
+        if failed_checks.is_empty() {
+            return error;
+        }
+        let Some(error) = error else {
+            let err = check::merge(Ok(()), failed_checks).unwrap_err();
+            return Some(phase(err.into()));
+        };
+        // The original error may be shared with reporters, so it's rendered into a new one.
+        let merge = |err: Arc<anyhow::Error>| {
+            let err = check::merge::<()>(Err(anyhow::format_err!("{err:#}")), failed_checks);
+            Arc::new(err.unwrap_err())
+        };
+        Some(match error {
+            Self::CreateSuite(err) => Self::CreateSuite(merge(err)),
+            Self::BeforeAll(err) => Self::BeforeAll(merge(err)),
+            Self::BeforeEach(err) => Self::BeforeEach(merge(err)),
+            Self::AfterEach(err) => Self::AfterEach(merge(err)),
+            Self::AfterAll(err) => Self::AfterAll(merge(err)),
+            Self::Test(err) => Self::Test(merge(err)),
+            Self::Cancelled(err) => Self::Cancelled(merge(err)),
+        })
+    }
+
+    /// Returns the underlying error.
+    pub fn inner(&self) -> &anyhow::Error {
+        match self {
+            Self::CreateSuite(err)
+            | Self::BeforeAll(err)
+            | Self::BeforeEach(err)
+            | Self::AfterEach(err)
+            | Self::AfterAll(err)
+            | Self::Test(err)
+            | Self::Cancelled(err) => err,
+        }
+    }
 }
 
 /// Re-exports for procedural macros.
 #[doc(hidden)]
 pub mod __private_reexports {
+    pub use anyhow;
     pub use async_trait::async_trait;
+    pub use tokio::sync::RwLock;
+
+    pub use crate::{
+        check::record_failure,
+        eventually::Retry,
+        fixture::{run_body, setup_failed, teardown},
+    };
 }
@@ -0,0 +1,80 @@
+use std::{
+    fmt::Write as _,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use futures::future::Either;
+use tracing::field::{Field, Visit};
+use tracing_subscriber::layer::Context;
+
+tokio::task_local! {
+    static CAPTURED_LOGS: Arc<Mutex<Vec<String>>>;
+}
+
+/// [`tracing_subscriber::Layer`] that captures log events emitted while a test is running.
+///
+/// Captured lines are stored in [`TestResult::logs`](crate::TestResult::logs).
+/// Only events emitted from the task that runs the test and from the tasks it spawns with
+/// [`spawn`](crate::spawn) are captured: events from other tasks are not attributed to
+/// any test.
+///
+/// ```no_run
+/// use tracing_subscriber::layer::SubscriberExt as _;
+///
+/// let subscriber = tracing_subscriber::registry().with(e2e::LogCaptureLayer);
+/// tracing::subscriber::set_global_default(subscriber).unwrap();
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogCaptureLayer;
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for LogCaptureLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let _ = CAPTURED_LOGS.try_with(|logs| {
+            let metadata = event.metadata();
+            let mut visitor = LineVisitor::default();
+            event.record(&mut visitor);
+            let line = format!(
+                "{:>5} {}: {}{}",
+                metadata.level(),
+                metadata.target(),
+                visitor.message,
+                visitor.fields
+            );
+            logs.lock().unwrap().push(line);
+        });
+    }
+}
+
+#[derive(Debug, Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            write!(self.message, "{:?}", value).unwrap();
+        } else {
+            write!(self.fields, " {}={:?}", field.name(), value).unwrap();
+        }
+    }
+}
+
+/// Runs the future, collecting the logs captured by [`LogCaptureLayer`].
+pub(crate) async fn capture<F: Future>(future: F) -> (F::Output, Vec<String>) {
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let output = CAPTURED_LOGS.scope(logs.clone(), future).await;
+    let logs = std::mem::take(&mut *logs.lock().unwrap());
+    (output, logs)
+}
+
+/// Wraps the future so that it's captured into the same logs as the current task, e.g.
+/// to attribute events of a spawned task to the test that spawned it.
+pub(crate) fn inherit<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    match CAPTURED_LOGS.try_with(Arc::clone) {
+        Ok(logs) => Either::Left(CAPTURED_LOGS.scope(logs, future)),
+        Err(_) => Either::Right(future),
+    }
+}
@@ -0,0 +1,94 @@
+use serde::Serialize;
+
+use crate::TestSuiteId;
+
+/// Static information about a test, available without creating the suite.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct TestMetadata {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub ignore: bool,
+    pub only: bool,
+}
+
+impl TestMetadata {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tags: Vec::new(),
+            ignore: false,
+            only: false,
+        }
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_ignore(mut self, ignore: bool) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
+    pub fn with_only(mut self, only: bool) -> Self {
+        self.only = only;
+        self
+    }
+}
+
+/// Static information about a test suite produced by a factory.
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct TestSuiteMetadata {
+    pub id: TestSuiteId,
+    pub name: String,
+    pub tests: Vec<TestMetadata>,
+}
+
+impl TestSuiteMetadata {
+    pub fn new(id: TestSuiteId, tests: Vec<TestMetadata>) -> Self {
+        Self {
+            name: id.to_string(),
+            id,
+            tests,
+        }
+    }
+}
+
+/// Output format for the list mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListFormat {
+    #[default]
+    Plain,
+    Json,
+}
+
+pub(crate) fn render_list(suites: &[TestSuiteMetadata], format: ListFormat) -> String {
+    match format {
+        ListFormat::Plain => {
+            let mut lines = Vec::new();
+            for suite in suites {
+                lines.push(suite.name.clone());
+                for test in &suite.tests {
+                    let mut line = format!("  {}", test.name);
+                    if test.ignore {
+                        line.push_str(" [ignored]");
+                    }
+                    if test.only {
+                        line.push_str(" [only]");
+                    }
+                    if !test.tags.is_empty() {
+                        line.push_str(&format!(" [tags: {}]", test.tags.join(", ")));
+                    }
+                    lines.push(line);
+                }
+            }
+            lines.join("\n")
+        }
+        ListFormat::Json => {
+            serde_json::to_string_pretty(suites).expect("Metadata is always serializable")
+        }
+    }
+}
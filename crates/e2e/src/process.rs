@@ -0,0 +1,475 @@
+//! Running the system under test as a local process.
+
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    future::Future,
+    io::Write as _,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::{ExitStatus, Stdio},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+use regex::Regex;
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncRead, BufReader},
+    process::Child,
+    sync::watch,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::scope;
+
+type HealthCheck = Box<dyn Fn() -> BoxFuture<'static, anyhow::Result<()>> + Send + Sync>;
+
+/// Size of the tail of the output kept in memory; the full output is in the log artifact.
+const OUTPUT_TAIL_BYTES: usize = 64 * 1024;
+
+/// Processes being supervised along with their stop tokens, so that they can be killed
+/// before the process exits without unwinding, e.g. on a second interrupt signal.
+static RUNNING: Mutex<Vec<(u64, Option<u32>, CancellationToken)>> = Mutex::new(Vec::new());
+
+enum ReadinessKind {
+    Spawned,
+    Addr(SocketAddr),
+    LogLine(String),
+    Check(HealthCheck),
+}
+
+/// Condition a [`Service`] must meet before it's considered started.
+pub struct Readiness(ReadinessKind);
+
+impl Readiness {
+    /// The service is ready as soon as the process is spawned.
+    pub fn spawned() -> Self {
+        Self(ReadinessKind::Spawned)
+    }
+
+    /// The service accepts TCP connections on the local port.
+    pub fn port(port: u16) -> Self {
+        Self::addr(SocketAddr::from(([127, 0, 0, 1], port)))
+    }
+
+    /// The service accepts TCP connections on the address.
+    pub fn addr(addr: SocketAddr) -> Self {
+        Self(ReadinessKind::Addr(addr))
+    }
+
+    /// A line of the service stdout or stderr matches the regex.
+    pub fn log_line(pattern: impl Into<String>) -> Self {
+        Self(ReadinessKind::LogLine(pattern.into()))
+    }
+
+    /// The health check succeeds, e.g. a request to the status endpoint of the service.
+    pub fn check<F, Fut>(check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        Self(ReadinessKind::Check(Box::new(move || Box::pin(check()))))
+    }
+}
+
+impl fmt::Debug for Readiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            ReadinessKind::Spawned => write!(f, "Spawned"),
+            ReadinessKind::Addr(addr) => write!(f, "Addr({addr})"),
+            ReadinessKind::LogLine(pattern) => write!(f, "LogLine({pattern:?})"),
+            ReadinessKind::Check(_) => write!(f, "Check"),
+        }
+    }
+}
+
+/// Readiness with the regex compiled.
+enum Probe {
+    Spawned,
+    Addr(SocketAddr),
+    LogLine(Regex),
+    Check(HealthCheck),
+}
+
+impl Probe {
+    fn new(readiness: Readiness) -> anyhow::Result<Self> {
+        Ok(match readiness.0 {
+            ReadinessKind::Spawned => Self::Spawned,
+            ReadinessKind::Addr(addr) => Self::Addr(addr),
+            ReadinessKind::LogLine(pattern) => Self::LogLine(
+                Regex::new(&pattern)
+                    .with_context(|| format!("Invalid readiness pattern {pattern:?}"))?,
+            ),
+            ReadinessKind::Check(check) => Self::Check(check),
+        })
+    }
+
+    async fn check(&self, service: &Service) -> anyhow::Result<()> {
+        match self {
+            Self::Spawned => Ok(()),
+            Self::Addr(addr) => tokio::net::TcpStream::connect(addr)
+                .await
+                .map(drop)
+                .with_context(|| format!("{addr} does not accept connections")),
+            Self::LogLine(regex) => {
+                let output = service.output();
+                anyhow::ensure!(
+                    output.lines().any(|line| regex.is_match(line)),
+                    "No output line matches {regex}"
+                );
+                Ok(())
+            }
+            Self::Check(check) => check().await,
+        }
+    }
+}
+
+/// Builder of a [`Service`].
+#[derive(Debug)]
+pub struct ServiceBuilder {
+    name: String,
+    command: std::process::Command,
+    readiness: Readiness,
+    startup_timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl ServiceBuilder {
+    pub fn new(name: impl Into<String>, command: std::process::Command) -> Self {
+        Self {
+            name: name.into(),
+            command,
+            readiness: Readiness::spawned(),
+            startup_timeout: Duration::from_secs(30),
+            poll_interval: Duration::from_millis(100),
+        }
+    }
+
+    pub fn with_readiness(mut self, readiness: Readiness) -> Self {
+        self.readiness = readiness;
+        self
+    }
+
+    /// Sets the time the service has to become ready, 30 seconds by default.
+    pub fn with_startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    /// Sets the interval between readiness checks, 100 milliseconds by default.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Spawns the process and waits for it to become ready.
+    ///
+    /// The process is owned by the current test, or by the current suite when called
+    /// from the constructor or `before_all`/`after_all` hooks. Its stdout and stderr are
+    /// stored in the `{name}.log` artifact and, if started from a test, in the test logs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a test suite.
+    pub async fn start(self) -> anyhow::Result<Service> {
+        let Self {
+            name,
+            command,
+            readiness,
+            startup_timeout,
+            poll_interval,
+        } = self;
+        let probe = Probe::new(readiness)?;
+        let (token, artifacts) = scope::current("process::ServiceBuilder::start", |scope| {
+            (scope.token().clone(), scope.artifacts().clone())
+        });
+
+        let log_path = artifacts
+            .attach_bytes(&format!("{name}.log"), b"")
+            .with_context(|| format!("Failed to create the log of service {name}"))?;
+        let log = OpenOptions::new()
+            .append(true)
+            .open(&log_path)
+            .with_context(|| format!("Failed to open {}", log_path.display()))?;
+        let output = Arc::new(Mutex::new(Output {
+            text: String::new(),
+            log,
+        }));
+
+        let mut child = tokio::process::Command::from(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to spawn service {name}"))?;
+        let pid = child.id();
+        if let Some(stdout) = child.stdout.take() {
+            let capture = capture_output(name.clone(), "stdout", stdout, output.clone());
+            scope::spawn(capture);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let capture = capture_output(name.clone(), "stderr", stderr, output.clone());
+            scope::spawn(capture);
+        }
+
+        // The process is killed once the owner finishes or the run is interrupted.
+        let stop = token.child_token();
+        let (exit_sender, exit) = watch::channel(None);
+        let running = Running::register(pid, stop.clone());
+        scope::spawn(supervise(child, stop.clone(), exit_sender, running));
+
+        let service = Service {
+            name,
+            pid,
+            output,
+            log_path,
+            stop,
+            exit,
+        };
+        service
+            .wait_ready(&probe, startup_timeout, poll_interval, &token)
+            .await?;
+        Ok(service)
+    }
+}
+
+/// Output of a service, mirrored to its log artifact.
+#[derive(Debug)]
+struct Output {
+    /// Last lines of the output, up to [`OUTPUT_TAIL_BYTES`].
+    text: String,
+    log: File,
+}
+
+impl Output {
+    fn push_line(&mut self, line: &str) {
+        self.text.push_str(line);
+        self.text.push('\n');
+        if self.text.len() > OUTPUT_TAIL_BYTES {
+            let excess = self.text.len() - OUTPUT_TAIL_BYTES;
+            // Drop whole lines; a single line longer than the tail is dropped entirely.
+            let end = self.text.as_bytes()[excess..]
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .map_or(self.text.len(), |pos| excess + pos + 1);
+            self.text.drain(..end);
+        }
+        // The log is best effort: the tail of the output is still available in memory.
+        let _ = self.log.write_all(format!("{line}\n").as_bytes());
+    }
+}
+
+async fn capture_output(
+    name: String,
+    stream: &'static str,
+    reader: impl AsyncRead + Unpin,
+    output: Arc<Mutex<Output>>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    // Lines are read as bytes, as the output of the process is not necessarily UTF-8.
+    while let Ok(read) = reader.read_until(b'\n', &mut buf).await {
+        if read == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        tracing::info!(service = %name, stream, "{line}");
+        output.lock().unwrap().push_line(line);
+        buf.clear();
+    }
+}
+
+/// Entry of a supervised process in [`RUNNING`], removed once dropped.
+struct Running {
+    id: u64,
+}
+
+impl Running {
+    fn register(pid: Option<u32>, stop: CancellationToken) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        RUNNING.lock().unwrap().push((id, pid, stop));
+        Self { id }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING.lock().unwrap().retain(|(id, ..)| *id != self.id);
+    }
+}
+
+/// Kills all the supervised processes, waiting up to `timeout` for them to be reaped.
+///
+/// The processes are also killed directly where supported, as the runtime supervising them
+/// may be blocked.
+pub(crate) async fn kill_all(timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    for (_, pid, stop) in RUNNING.lock().unwrap().iter() {
+        stop.cancel();
+        #[cfg(unix)]
+        if let Some(pid) = pid.and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+            // SAFETY: `kill` has no memory safety preconditions. The pid is unregistered as
+            // soon as the process is reaped, which makes its reuse in between unlikely.
+            unsafe {
+                libc::kill(pid, libc::SIGKILL);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = pid;
+    }
+    while !RUNNING.lock().unwrap().is_empty() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// Waits for the process to exit, killing it once `stop` is cancelled.
+async fn supervise(
+    mut child: Child,
+    stop: CancellationToken,
+    exit: watch::Sender<Option<ExitStatus>>,
+    running: Running,
+) {
+    let status = tokio::select! {
+        status = child.wait() => Some(status),
+        _ = stop.cancelled() => None,
+    };
+    let status = match status {
+        Some(status) => status,
+        None => {
+            // Fails if the process has already exited, which is fine.
+            let _ = child.start_kill();
+            child.wait().await
+        }
+    };
+    // The process is reaped, so it must no longer be killed by its pid.
+    drop(running);
+    match status {
+        Ok(status) => {
+            exit.send_replace(Some(status));
+        }
+        Err(err) => tracing::warn!("Failed to wait for the service process: {err}"),
+    }
+}
+
+/// Process of the system under test, spawned by [`ServiceBuilder::start`].
+///
+/// The process is killed when the service is dropped, when its owner (a test or a suite)
+/// finishes, or when the run is interrupted, whichever happens first. A service started
+/// in the constructor or `before_all` hook of a suite is available until its `after_all`
+/// hook finishes.
+///
+/// ```no_run
+/// use e2e::process::{Readiness, Service};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let mut command = std::process::Command::new("target/debug/server");
+/// command.arg("--port=8080");
+/// let server = Service::builder("server", command)
+///     .with_readiness(Readiness::port(8080))
+///     .start()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Service {
+    name: String,
+    pid: Option<u32>,
+    output: Arc<Mutex<Output>>,
+    log_path: PathBuf,
+    stop: CancellationToken,
+    exit: watch::Receiver<Option<ExitStatus>>,
+}
+
+impl Service {
+    pub fn builder(name: impl Into<String>, command: std::process::Command) -> ServiceBuilder {
+        ServiceBuilder::new(name, command)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the process identifier.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Returns the last lines of stdout and stderr of the process captured so far (up to
+    /// 64 KiB); the full output is stored in the log artifact.
+    pub fn output(&self) -> String {
+        self.output.lock().unwrap().text.clone()
+    }
+
+    /// Returns the path of the artifact the output is stored in.
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+
+    /// Returns the exit status of the process, if it has exited.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        *self.exit.borrow()
+    }
+
+    /// Kills the process and waits for it to exit.
+    pub async fn stop(&mut self) -> anyhow::Result<ExitStatus> {
+        self.stop.cancel();
+        let status = self
+            .exit
+            .wait_for(Option::is_some)
+            .await
+            .with_context(|| format!("Service {} was aborted", self.name))?;
+        Ok(status.expect("Waited for the status"))
+    }
+
+    async fn wait_ready(
+        &self,
+        probe: &Probe,
+        timeout: Duration,
+        interval: Duration,
+        token: &CancellationToken,
+    ) -> anyhow::Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let checked = tokio::time::timeout_at(deadline.into(), probe.check(self)).await;
+            let err = match checked {
+                Ok(Ok(())) => return Ok(()),
+                Ok(Err(err)) => err,
+                Err(_) => anyhow::format_err!("Readiness check did not finish"),
+            };
+            if let Some(status) = self.exit_status() {
+                anyhow::bail!(
+                    "Service {} exited with {status} before becoming ready, output:\n{}",
+                    self.name,
+                    self.output()
+                );
+            }
+            if Instant::now() >= deadline {
+                return Err(err.context(format!(
+                    "Service {} is not ready after {timeout:?}",
+                    self.name
+                )));
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = token.cancelled() => anyhow::bail!("Startup of service {} was cancelled", self.name),
+            }
+        }
+    }
+}
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        self.stop.cancel();
+    }
+}
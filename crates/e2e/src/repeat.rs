@@ -0,0 +1,62 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{TestId, TestSuiteResult};
+
+/// Defines how suites are instantiated when tests are repeated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RepeatMode {
+    /// Suite is created (and `before_all`/`after_all` are run) for each iteration.
+    #[default]
+    Fresh,
+    /// Suite is created once, and its tests are repeated within it.
+    Reuse,
+}
+
+/// Outcomes of a test across all the iterations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RepeatStats {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+/// Aggregated statistics of a run with repeated tests.
+#[derive(Debug, Clone, Default)]
+pub struct RepeatReport {
+    pub tests: BTreeMap<TestId, RepeatStats>,
+}
+
+impl RepeatReport {
+    pub fn new(results: &[TestSuiteResult]) -> Self {
+        let mut tests: BTreeMap<TestId, RepeatStats> = BTreeMap::new();
+        for test in results.iter().flat_map(|suite| &suite.tests) {
+            if test.ignored {
+                continue;
+            }
+            let stats = tests.entry(test.id.clone()).or_default();
+            if test.passed() {
+                stats.passed += 1;
+            } else {
+                stats.failed += 1;
+            }
+        }
+        Self { tests }
+    }
+}
+
+impl fmt::Display for RepeatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Repeat statistics:")?;
+        for (id, stats) in &self.tests {
+            let runs = stats.passed + stats.failed;
+            writeln!(
+                f,
+                "  - {id}: {}/{runs} passed, {} failed ({:.1}% failure rate)",
+                stats.passed,
+                stats.failed,
+                stats.failed as f64 * 100.0 / runs as f64
+            )?;
+        }
+        Ok(())
+    }
+}
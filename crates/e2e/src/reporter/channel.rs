@@ -0,0 +1,104 @@
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{TestError, TestId, TestSuiteId, TestSuiteResult, reporter::Reporter};
+
+/// Owned representation of a reporter callback.
+///
+/// Events are used to deliver reporter callbacks to [`AsyncReporter`]s that run on
+/// their own task.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ReporterEvent {
+    TestSuiteCreationStarted(TestSuiteId),
+    TestSuiteIgnored(TestSuiteId),
+    TestSuiteCreationFinished(TestSuiteId, Option<TestError>),
+    TestSuiteStarted(TestSuiteId),
+    TestSuiteFinished(TestSuiteId, TestSuiteResult),
+    TestStarted(TestId),
+    TestIgnored(TestId),
+    TestFinished(TestId, Option<TestError>),
+    RunFinished(Vec<TestSuiteResult>),
+}
+
+impl ReporterEvent {
+    /// Invokes the callback of a synchronous reporter that corresponds to this event.
+    pub fn dispatch_to(&self, reporter: &mut dyn Reporter) {
+        match self {
+            Self::TestSuiteCreationStarted(id) => reporter.on_test_suite_creation_started(id),
+            Self::TestSuiteIgnored(id) => reporter.on_test_suite_ignored(id),
+            Self::TestSuiteCreationFinished(id, error) => {
+                reporter.on_test_suite_creation_finished(id, error.as_ref())
+            }
+            Self::TestSuiteStarted(id) => reporter.on_test_suite_start(id),
+            Self::TestSuiteFinished(id, result) => reporter.on_test_suite_end(id, result),
+            Self::TestStarted(id) => reporter.on_test_start(id),
+            Self::TestIgnored(id) => reporter.on_test_ignored(id),
+            Self::TestFinished(id, error) => reporter.on_test_end(id, error.as_ref()),
+            Self::RunFinished(results) => reporter.on_run_finished(results),
+        }
+    }
+}
+
+/// Reporter that processes events on its own task.
+///
+/// Unlike [`Reporter`], async reporters never block the test execution: events are
+/// queued, so slow sinks (e.g. file or network I/O) only delay the reporter itself.
+/// The runner waits for all the queued events to be processed before the run ends.
+#[async_trait::async_trait]
+pub trait AsyncReporter: Send + 'static {
+    fn name(&self) -> &'static str;
+    async fn on_event(&mut self, event: ReporterEvent);
+}
+
+/// Sending half of an [`AsyncReporter`] spawned on its own task.
+///
+/// Handles are cheap to clone, so events can be reported from several tasks at once.
+#[derive(Debug, Clone)]
+pub(crate) struct ReporterHandle {
+    name: &'static str,
+    sender: mpsc::UnboundedSender<ReporterEvent>,
+}
+
+impl ReporterHandle {
+    pub(crate) fn send(&self, event: ReporterEvent) {
+        if self.sender.send(event).is_err() {
+            tracing::warn!("Reporter {} has stopped, event is dropped", self.name);
+        }
+    }
+}
+
+/// Async reporter that is spawned on its own task.
+#[derive(Debug)]
+pub(crate) struct SpawnedReporter {
+    handle: ReporterHandle,
+    task: JoinHandle<()>,
+}
+
+impl SpawnedReporter {
+    pub(crate) fn spawn(mut reporter: Box<dyn AsyncReporter>) -> Self {
+        let name = reporter.name();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                reporter.on_event(event).await;
+            }
+        });
+        Self {
+            handle: ReporterHandle { name, sender },
+            task,
+        }
+    }
+
+    pub(crate) fn handle(&self) -> &ReporterHandle {
+        &self.handle
+    }
+
+    /// Waits until all the sent events are processed.
+    pub(crate) async fn finish(self) {
+        let name = self.handle.name;
+        drop(self.handle);
+        if let Err(err) = self.task.await {
+            tracing::error!("Reporter {} failed: {}", name, err);
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use std::{collections::HashMap, time::Duration};
+
 use console::Term;
 
-use crate::{TestError, TestSuiteResult, reporter::Reporter};
+use crate::{TestError, TestId, TestResult, TestSuiteId, TestSuiteResult, reporter::Reporter};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TestStateMarker {
@@ -24,7 +26,7 @@ impl TestStateMarker {
 #[derive(Debug)]
 struct TestState {
     marker: TestStateMarker,
-    name: String,
+    id: TestId,
     error: Option<String>, // TODO: Should not be string
 }
 
@@ -49,54 +51,124 @@ impl TestSuiteStateMarker {
     }
 }
 
+/// State of a suite that is displayed in the live region.
+///
+/// Passed and ignored tests are only counted: for large suites, keeping and redrawing
+/// every test would make each update proportional to the size of the suite.
 #[derive(Debug)]
 struct TestSuiteState {
-    name: String,
+    /// Position of the suite in the live region.
+    order: u64,
+    id: TestSuiteId,
     marker: TestSuiteStateMarker,
+    /// Tests that are either running or failed.
     tests: Vec<TestState>,
+    passed: usize,
+    ignored: usize,
     error: Option<String>, // TODO: Should not be string
 }
 
+impl TestSuiteState {
+    fn render(&self, lines: &mut Vec<String>) {
+        lines.push(self.header());
+        if let Some(error) = &self.error {
+            lines.push("  - Error:".to_string());
+            for line in error.lines() {
+                lines.push(format!("    - {}", line));
+            }
+        }
+        for test in &self.tests {
+            let test_marker = test.marker.emoji();
+            let test_name = &test.id.test;
+            if let Some(err) = &test.error {
+                lines.push(format!("  - {} {} error:", test_marker, test_name));
+                for line in err.lines() {
+                    lines.push(format!("    | {}", line));
+                }
+            } else {
+                lines.push(format!("  - {} {}", test_marker, test_name));
+            }
+        }
+    }
+
+    fn header(&self) -> String {
+        let mut header = format!("{} Test Suite: {}", self.marker.emoji(), self.id);
+        if self.passed > 0 || self.ignored > 0 {
+            header.push_str(&format!(
+                " ({} passed, {} ignored)",
+                self.passed, self.ignored
+            ));
+        }
+        header
+    }
+}
+
+/// Reporter that renders the progress of the run to the terminal.
+///
+/// Output consists of two parts: suites that are finished are printed once (successful
+/// ones as a single line, and failed ones in full), while suites that are in progress are
+/// kept in the "live region" at the bottom of the output that is redrawn on every update.
+/// If the output is not a terminal, the live region is not drawn at all.
 #[derive(Debug)]
 pub struct ConsoleReporter {
     term: Term,
+    /// Whether the live region can be redrawn.
+    interactive: bool,
     lines_written: usize,
-    suites: Vec<TestSuiteState>,
+    suites: HashMap<TestSuiteId, TestSuiteState>,
+    /// Counter used to preserve the order of suites in the live region.
+    next_order: u64,
+    /// Number of the slowest tests to show in the final summary.
+    slowest_tests: usize,
 }
 
 impl ConsoleReporter {
+    const DEFAULT_SLOWEST_TESTS: usize = 5;
+
     pub fn new() -> Self {
+        let term = Term::stdout();
         ConsoleReporter {
-            term: Term::stdout(),
+            interactive: term.is_term(),
+            term,
             lines_written: 0,
-            suites: Vec::new(),
+            suites: HashMap::new(),
+            next_order: 0,
+            slowest_tests: Self::DEFAULT_SLOWEST_TESTS,
         }
     }
 
+    pub fn with_slowest_tests(mut self, slowest_tests: usize) -> Self {
+        self.slowest_tests = slowest_tests;
+        self
+    }
+
+    /// Writes the output to the specified terminal instead of stdout.
+    ///
+    /// The live region is only drawn if the terminal is interactive.
+    pub fn with_term(mut self, term: Term) -> Self {
+        self.interactive = term.is_term();
+        self.term = term;
+        self
+    }
+
+    /// Forces drawing the live region on or off, regardless of whether the output is a
+    /// terminal.
+    pub fn with_live_region(mut self, enabled: bool) -> Self {
+        self.interactive = enabled;
+        self
+    }
+
+    /// Redraws the live region.
     pub fn write(&mut self) {
+        if !self.interactive {
+            return;
+        }
         self.term.clear_last_lines(self.lines_written).unwrap();
+        let mut suites: Vec<_> = self.suites.values().collect();
+        suites.sort_by_key(|suite| suite.order);
         let mut lines = Vec::new();
-        for suite in &self.suites {
-            let marker = suite.marker.emoji();
-            lines.push(format!("{} Test Suite: {}", marker, suite.name));
-            if let Some(error) = &suite.error {
-                lines.push("  - Error:".to_string());
-                for line in error.lines() {
-                    lines.push(format!("    - {}", line));
-                }
-            }
-            for test in &suite.tests {
-                let test_marker = test.marker.emoji();
-                let test_name = &test.name;
-                if let Some(err) = &test.error {
-                    lines.push(format!("  - {} {} error:", test_marker, test_name));
-                    for line in err.lines() {
-                        lines.push(format!("    | {}", line));
-                    }
-                } else {
-                    lines.push(format!("  - {} {}", test_marker, test_name));
-                }
-            }
+        for suite in suites {
+            suite.render(&mut lines);
         }
         self.lines_written = lines.len();
         for line in lines {
@@ -104,55 +176,272 @@ impl ConsoleReporter {
         }
     }
 
-    fn add_test_suite(&mut self, name: &str, marker: TestSuiteStateMarker) {
+    /// Prints lines above the live region, so that they are never redrawn.
+    fn write_permanent(&mut self, lines: &[String]) {
+        if self.interactive {
+            self.term.clear_last_lines(self.lines_written).unwrap();
+            self.lines_written = 0;
+        }
+        for line in lines {
+            self.term.write_line(line).unwrap();
+        }
+        self.write();
+    }
+
+    fn add_test_suite(&mut self, id: &TestSuiteId, marker: TestSuiteStateMarker) {
         let state = TestSuiteState {
-            name: name.to_string(),
+            order: self.next_order,
+            id: id.clone(),
             marker,
             tests: Vec::new(),
+            passed: 0,
+            ignored: 0,
             error: None,
         };
-        self.suites.push(state);
+        self.next_order += 1;
+        self.suites.insert(id.clone(), state);
     }
 
     fn update_test_suite(
         &mut self,
-        name: &str,
+        id: &TestSuiteId,
         marker: TestSuiteStateMarker,
         error: Option<String>, // TODO: should not be string
     ) {
-        if let Some(suite) = self.suites.iter_mut().find(|s| s.name == name) {
+        if let Some(suite) = self.suites.get_mut(id) {
             suite.marker = marker;
             suite.error = error;
         }
     }
 
-    fn add_test(&mut self, name: &str, marker: TestStateMarker) {
-        if let Some(suite) = self.suites.last_mut() {
-            let state = TestState {
-                marker,
-                name: name.to_string(),
-                error: None,
-            };
-            suite.tests.push(state);
+    /// Removes the suite from the live region if it's done.
+    ///
+    /// Successful suites are collapsed into a single line, and failed suites are printed
+    /// in full, so that redrawing the live region doesn't get slower with every failure.
+    fn finish_test_suite(&mut self, id: &TestSuiteId) {
+        let Some(suite) = self.suites.get(id) else {
+            return;
+        };
+        let lines = match suite.marker {
+            TestSuiteStateMarker::Success | TestSuiteStateMarker::Ignored => {
+                vec![suite.header()]
+            }
+            TestSuiteStateMarker::Error => {
+                let mut lines = Vec::new();
+                suite.render(&mut lines);
+                lines
+            }
+            _ => {
+                self.write();
+                return;
+            }
+        };
+        self.suites.remove(id);
+        self.write_permanent(&lines);
+    }
+
+    fn add_test(&mut self, id: &TestId, marker: TestStateMarker) {
+        if let Some(suite) = self.suites.get_mut(&id.suite_id()) {
+            if marker == TestStateMarker::Ignored {
+                suite.ignored += 1;
+            } else {
+                let state = TestState {
+                    marker,
+                    id: id.clone(),
+                    error: None,
+                };
+                suite.tests.push(state);
+            }
         } else {
-            eprintln!("No test suite found to add test: {}", name);
+            eprintln!("No test suite found to add test: {}", id);
         }
     }
 
-    fn update_test(&mut self, name: &str, marker: TestStateMarker, error: Option<String>)
+    fn update_test(&mut self, id: &TestId, marker: TestStateMarker, error: Option<String>)
     // TODO: should not be string
     {
-        if let Some(suite) = self.suites.last_mut() {
-            if let Some(test) = suite.tests.iter_mut().find(|t| t.name == name) {
-                test.marker = marker;
-                test.error = error;
+        if let Some(suite) = self.suites.get_mut(&id.suite_id()) {
+            if let Some(idx) = suite
+                .tests
+                .iter()
+                .position(|t| &t.id == id && t.marker == TestStateMarker::Running)
+            {
+                if marker == TestStateMarker::Success {
+                    suite.tests.remove(idx);
+                    suite.passed += 1;
+                } else {
+                    let test = &mut suite.tests[idx];
+                    test.marker = marker;
+                    test.error = error;
+                }
             } else {
-                eprintln!("No test found to update: {}", name);
+                eprintln!("No test found to update: {}", id);
             }
         } else {
-            eprintln!("No test suite found to update test: {}", name);
+            eprintln!("No test suite found to update test: {}", id);
         }
     }
+
+    fn write_summary(&mut self, results: &[TestSuiteResult]) {
+        let all_tests = || results.iter().flat_map(|suite| &suite.tests);
+        let ignored = all_tests().filter(|test| test.ignored).count();
+        let cancelled = all_tests().filter(|test| test.cancelled()).count();
+        let failed = all_tests().filter(|test| !test.passed()).count() - cancelled;
+        let passed = all_tests().count() - ignored - failed - cancelled;
+
+        // Tests are only flaky if they were repeated and had different outcomes.
+        let mut outcomes: HashMap<&TestId, (bool, bool)> = HashMap::new();
+        for test in all_tests().filter(|test| !test.ignored && !test.cancelled()) {
+            let (passed, failed) = outcomes.entry(&test.id).or_default();
+            if test.passed() {
+                *passed = true;
+            } else {
+                *failed = true;
+            }
+        }
+        let flaky = outcomes
+            .values()
+            .filter(|(passed, failed)| *passed && *failed)
+            .count();
+
+        let mut totals =
+            format!("  - Passed: {passed}, failed: {failed}, flaky: {flaky}, ignored: {ignored}");
+        if cancelled > 0 {
+            totals.push_str(&format!(", cancelled: {cancelled}"));
+        }
+        let mut lines = vec![String::new(), "Summary:".to_string(), totals];
+
+        let failed_suites: Vec<_> = results.iter().filter(|suite| !suite.passed).collect();
+        if !failed_suites.is_empty() {
+            lines.push("Failed test suites:".to_string());
+            for suite in &failed_suites {
+                lines.push(format!(
+                    "  - {} {}",
+                    TestSuiteStateMarker::Error.emoji(),
+                    suite.name
+                ));
+                if let Some(err) = &suite.error {
+                    lines.push(format!("    | {}", first_line(err)));
+                }
+                for artifact in &suite.artifacts {
+                    lines.push(format!("    | artifact: {}", artifact.path.display()));
+                }
+                if let Some(dir) = &suite.temp_dir {
+                    lines.push(format!("    | temp dir: {}", dir.display()));
+                }
+                for test in suite.tests.iter().filter(|test| !test.passed()) {
+                    let err = test.error.as_ref().expect("Failed test must have an error");
+                    lines.push(format!("    - {}: {}", test.name, first_line(err)));
+                    for artifact in &test.artifacts {
+                        lines.push(format!("      | artifact: {}", artifact.path.display()));
+                    }
+                    if let Some(dir) = &test.temp_dir {
+                        lines.push(format!("      | temp dir: {}", dir.display()));
+                    }
+                }
+            }
+        }
+
+        let mut executed: Vec<(&str, &TestResult)> = results
+            .iter()
+            .flat_map(|suite| suite.tests.iter().map(|test| (suite.name.as_str(), test)))
+            .filter(|(_, test)| !test.ignored)
+            .collect();
+        executed.sort_by_key(|(_, test)| std::cmp::Reverse(test.duration));
+        if self.slowest_tests > 0 && !executed.is_empty() {
+            lines.push("Slowest tests:".to_string());
+            for (suite_name, test) in executed.iter().take(self.slowest_tests) {
+                lines.push(format!(
+                    "  - {} {} / {}",
+                    format_duration(test.duration),
+                    suite_name,
+                    test.name
+                ));
+            }
+        }
+
+        if !failed_suites.is_empty() {
+            lines.push("Re-run failed tests with:".to_string());
+            for args in rerun_args(&failed_suites) {
+                lines.push(format!("  {args}"));
+            }
+        }
+
+        for line in lines {
+            self.term.write_line(&line).unwrap();
+        }
+        // Summary is not a part of the redrawable area.
+        self.lines_written = 0;
+    }
+}
+
+/// Returns the first line of the error message, which is enough for the summary.
+fn first_line(error: &TestError) -> String {
+    error
+        .to_string()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:>8.2?}", duration)
+}
+
+/// Builds command line arguments that would select only the failed tests, one line per
+/// failed suite.
+///
+/// Test case filter applies to all the selected suites, so combining suites into a single
+/// line would either re-run tests that passed, or skip suites that failed as a whole.
+fn rerun_args(failed_suites: &[&TestSuiteResult]) -> Vec<String> {
+    // Suites may be reported several times when tests are repeated.
+    let mut suites: Vec<(&str, Vec<&TestSuiteResult>)> = Vec::new();
+    for suite in failed_suites {
+        match suites.iter_mut().find(|(name, _)| *name == suite.name) {
+            Some((_, results)) => results.push(suite),
+            None => suites.push((&suite.name, vec![suite])),
+        }
+    }
+
+    suites
+        .into_iter()
+        .map(|(name, results)| {
+            let mut args = format!(
+                "--test-suite-filter {}",
+                shell_quote(&exact_match_regex(&[name]))
+            );
+            // If a suite failed as a whole (e.g. in a hook), all of its tests have to be
+            // re-run.
+            let whole_suite_failed = results
+                .iter()
+                .any(|suite| suite.error.is_some() || suite.tests.iter().all(|test| test.passed()));
+            if !whole_suite_failed {
+                let mut test_names: Vec<&str> = results
+                    .iter()
+                    .flat_map(|suite| &suite.tests)
+                    .filter(|test| !test.passed())
+                    .map(|test| test.name.as_str())
+                    .collect();
+                test_names.sort_unstable();
+                test_names.dedup();
+                args.push_str(&format!(
+                    " --test-case-filter {}",
+                    shell_quote(&exact_match_regex(&test_names))
+                ));
+            }
+            args
+        })
+        .collect()
+}
+
+fn exact_match_regex(names: &[&str]) -> String {
+    let alternatives: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+    format!("^({})$", alternatives.join("|"))
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
 }
 
 impl Default for ConsoleReporter {
@@ -166,56 +455,54 @@ impl Reporter for ConsoleReporter {
         "ConsoleReporter"
     }
 
-    fn on_test_suite_creation_started(&mut self, name: &str) {
-        self.add_test_suite(name, TestSuiteStateMarker::Creating);
+    fn on_test_suite_creation_started(&mut self, id: &TestSuiteId) {
+        self.add_test_suite(id, TestSuiteStateMarker::Creating);
         self.write();
     }
 
-    fn on_test_suite_ignored(&mut self, name: &str) {
-        self.add_test_suite(name, TestSuiteStateMarker::Ignored);
-        self.write();
+    fn on_test_suite_ignored(&mut self, id: &TestSuiteId) {
+        self.add_test_suite(id, TestSuiteStateMarker::Ignored);
+        self.finish_test_suite(id);
     }
 
-    fn on_test_suite_creation_finished(&mut self, name: &str, error: Option<&TestError>) {
+    fn on_test_suite_creation_finished(&mut self, id: &TestSuiteId, error: Option<&TestError>) {
         if let Some(err) = error {
-            self.update_test_suite(name, TestSuiteStateMarker::Error, Some(err.to_string()));
+            self.update_test_suite(id, TestSuiteStateMarker::Error, Some(err.to_string()));
         }
         self.write();
     }
 
-    fn on_test_suite_start(&mut self, name: &str) {
-        self.update_test_suite(name, TestSuiteStateMarker::Running, None);
+    fn on_test_suite_start(&mut self, id: &TestSuiteId) {
+        self.update_test_suite(id, TestSuiteStateMarker::Running, None);
         self.write();
     }
 
-    fn on_test_suite_end(&mut self, name: &str, result: &TestSuiteResult) {
+    fn on_test_suite_end(&mut self, id: &TestSuiteId, result: &TestSuiteResult) {
         self.update_test_suite(
-            name,
-            if result.error.is_some() {
-                TestSuiteStateMarker::Error
-            } else {
+            id,
+            if result.passed {
                 TestSuiteStateMarker::Success
+            } else {
+                TestSuiteStateMarker::Error
             },
             result.error.as_ref().map(|e| e.to_string()),
         );
-        self.write();
-        // TODO: probably we can remove this suite from the list smth like
-        // self.suites.retain(|s| s.name != name);
+        self.finish_test_suite(id);
     }
 
-    fn on_test_start(&mut self, name: &str) {
-        self.add_test(name, TestStateMarker::Running);
+    fn on_test_start(&mut self, id: &TestId) {
+        self.add_test(id, TestStateMarker::Running);
         self.write();
     }
 
-    fn on_test_ignored(&mut self, name: &str) {
-        self.add_test(name, TestStateMarker::Ignored);
+    fn on_test_ignored(&mut self, id: &TestId) {
+        self.add_test(id, TestStateMarker::Ignored);
         self.write();
     }
 
-    fn on_test_end(&mut self, name: &str, error: Option<&TestError>) {
+    fn on_test_end(&mut self, id: &TestId, error: Option<&TestError>) {
         self.update_test(
-            name,
+            id,
             if error.is_some() {
                 TestStateMarker::Error
             } else {
@@ -223,5 +510,11 @@ impl Reporter for ConsoleReporter {
             },
             error.as_ref().map(|e| e.to_string()),
         );
+        self.write();
+    }
+
+    fn on_run_finished(&mut self, results: &[TestSuiteResult]) {
+        self.write();
+        self.write_summary(results);
     }
 }
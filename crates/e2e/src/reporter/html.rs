@@ -0,0 +1,261 @@
+use std::{
+    fmt::Write as _,
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{Artifact, TestError, TestResult, TestSuiteId, TestSuiteResult, reporter::Reporter};
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; }
+h1 { font-size: 1.4em; }
+#filter { width: 100%; padding: 0.4em; margin-bottom: 1em; box-sizing: border-box; }
+details { margin: 0.2em 0; }
+details.suite { border: 1px solid #ccc; border-radius: 4px; padding: 0.4em 0.8em; }
+details.test { margin-left: 1.5em; }
+summary { cursor: pointer; }
+.passed > summary .status { color: #1a7f37; }
+.failed > summary .status { color: #cf222e; }
+.ignored > summary .status { color: #9a6700; }
+.cancelled > summary .status { color: #6e7781; }
+.duration { color: #666; font-size: 0.9em; }
+pre { background: #f6f8fa; padding: 0.6em; overflow-x: auto; }
+"#;
+
+const SCRIPT: &str = r#"
+document.getElementById('filter').addEventListener('input', function (e) {
+  const query = e.target.value.toLowerCase();
+  for (const suite of document.querySelectorAll('details.suite')) {
+    let visible = 0;
+    for (const test of suite.querySelectorAll('details.test')) {
+      const match = test.dataset.name.toLowerCase().includes(query)
+        || suite.dataset.name.toLowerCase().includes(query)
+        || test.dataset.status === query;
+      test.style.display = match ? '' : 'none';
+      visible += match ? 1 : 0;
+    }
+    const suiteMatch = suite.dataset.name.toLowerCase().includes(query)
+      || suite.dataset.status === query;
+    suite.style.display = (visible > 0 || suiteMatch) ? '' : 'none';
+  }
+});
+"#;
+
+/// Reporter that writes a self-contained static HTML report once the run is finished.
+///
+/// The report contains collapsible suites with per-test statuses, durations, error
+/// chains, captured logs and links to artifacts, as well as a box to filter tests by name
+/// or status.
+#[derive(Debug)]
+pub struct HtmlReporter {
+    path: PathBuf,
+    /// Suites that were not run, which are not a part of the results.
+    ignored_suites: Vec<TestSuiteId>,
+}
+
+impl HtmlReporter {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            ignored_suites: Vec::new(),
+        }
+    }
+
+    /// Renders the report; artifact links are relative to `report_dir` when possible.
+    fn render(
+        results: &[TestSuiteResult],
+        ignored_suites: &[TestSuiteId],
+        report_dir: &Path,
+    ) -> String {
+        let all_tests = || results.iter().flat_map(|suite| &suite.tests);
+        let ignored = all_tests().filter(|test| test.ignored).count();
+        let cancelled = all_tests().filter(|test| test.cancelled()).count();
+        let failed = all_tests().filter(|test| !test.passed()).count() - cancelled;
+        let passed = all_tests().count() - ignored - failed - cancelled;
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Test report</title>\n");
+        writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>").unwrap();
+        writeln!(
+            html,
+            "<h1>Test report</h1>\n<p>Passed: {passed}, failed: {failed}, ignored: {ignored}, cancelled: {cancelled}</p>"
+        )
+        .unwrap();
+        html.push_str(
+            "<input id=\"filter\" type=\"search\" placeholder=\"Filter by name or status (passed, failed, ignored, cancelled)\">\n",
+        );
+        for suite in results {
+            Self::render_suite(&mut html, suite, report_dir);
+        }
+        for id in ignored_suites {
+            writeln!(
+                html,
+                "<details class=\"suite ignored\" data-name=\"{name}\" data-status=\"ignored\">\n\
+                 <summary><span class=\"status\">ignored</span> {name}</summary>\n</details>",
+                name = escape(&id.to_string()),
+            )
+            .unwrap();
+        }
+        writeln!(html, "<script>{SCRIPT}</script>\n</body>\n</html>").unwrap();
+        html
+    }
+
+    fn render_suite(html: &mut String, suite: &TestSuiteResult, report_dir: &Path) {
+        let status = if suite.passed { "passed" } else { "failed" };
+        let open = if suite.passed { "" } else { " open" };
+        writeln!(
+            html,
+            "<details class=\"suite {status}\" data-name=\"{name}\" data-status=\"{status}\"{open}>\n\
+             <summary><span class=\"status\">{status}</span> {name} \
+             <span class=\"duration\">{duration}</span></summary>",
+            name = escape(&suite.name),
+            duration = format_duration(suite.duration),
+        )
+        .unwrap();
+        if let Some(error) = &suite.error {
+            Self::render_error(html, error);
+        }
+        Self::render_artifacts(html, &suite.artifacts, report_dir);
+        for test in &suite.tests {
+            Self::render_test(html, test, report_dir);
+        }
+        html.push_str("</details>\n");
+    }
+
+    fn render_test(html: &mut String, test: &TestResult, report_dir: &Path) {
+        let status = if test.ignored {
+            "ignored"
+        } else if test.cancelled() {
+            "cancelled"
+        } else if test.passed() {
+            "passed"
+        } else {
+            "failed"
+        };
+        writeln!(
+            html,
+            "<details class=\"test {status}\" data-name=\"{name}\" data-status=\"{status}\">\n\
+             <summary><span class=\"status\">{status}</span> {name} \
+             <span class=\"duration\">{duration}</span></summary>",
+            name = escape(&test.name),
+            duration = format_duration(test.duration),
+        )
+        .unwrap();
+        if let Some(error) = &test.error {
+            Self::render_error(html, error);
+        }
+        if !test.logs.is_empty() {
+            html.push_str("<p>Logs:</p>\n<pre>");
+            for line in &test.logs {
+                writeln!(html, "{}", escape(line)).unwrap();
+            }
+            html.push_str("</pre>\n");
+        }
+        Self::render_artifacts(html, &test.artifacts, report_dir);
+        html.push_str("</details>\n");
+    }
+
+    fn render_artifacts(html: &mut String, artifacts: &[Artifact], report_dir: &Path) {
+        if artifacts.is_empty() {
+            return;
+        }
+        html.push_str("<p>Artifacts:</p>\n<ul>\n");
+        for artifact in artifacts {
+            let href = match artifact.path.strip_prefix(report_dir) {
+                Ok(relative) => encode_path(relative),
+                Err(_) => format!("file:///{}", encode_path(&artifact.path)),
+            };
+            writeln!(
+                html,
+                "<li><a href=\"{}\">{}</a></li>",
+                escape(&href),
+                escape(&artifact.name)
+            )
+            .unwrap();
+        }
+        html.push_str("</ul>\n");
+    }
+
+    fn render_error(html: &mut String, error: &TestError) {
+        writeln!(
+            html,
+            "<p>Failed in <code>{}</code>:</p>\n<ol>",
+            error.phase()
+        )
+        .unwrap();
+        for cause in error.inner().chain() {
+            writeln!(html, "<li><pre>{}</pre></li>", escape(&cause.to_string())).unwrap();
+        }
+        html.push_str("</ol>\n");
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2?}", duration)
+}
+
+/// Converts a path to the path of a URL, percent-encoding each component.
+fn encode_path(path: &Path) -> String {
+    let components: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::RootDir => None,
+            Component::Normal(name) => Some(percent_encode(&name.to_string_lossy())),
+            other => Some(other.as_os_str().to_string_lossy().into_owned()),
+        })
+        .collect();
+    components.join("/")
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            write!(encoded, "%{byte:02X}").unwrap();
+        }
+    }
+    encoded
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Reporter for HtmlReporter {
+    fn name(&self) -> &'static str {
+        "HtmlReporter"
+    }
+
+    fn on_test_suite_ignored(&mut self, id: &TestSuiteId) {
+        self.ignored_suites.push(id.clone());
+    }
+
+    fn on_run_finished(&mut self, results: &[TestSuiteResult]) {
+        let report_dir = std::path::absolute(&self.path)
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        let html = Self::render(results, &self.ignored_suites, &report_dir);
+        if let Err(err) = std::fs::write(&self.path, html) {
+            tracing::error!(
+                "Failed to write HTML report to {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
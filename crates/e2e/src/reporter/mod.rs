@@ -1,19 +1,56 @@
-use std::fmt;
+use std::{fmt, sync::Mutex};
 
+pub(super) mod channel;
 pub(super) mod console;
+pub(super) mod html;
+pub(super) mod tap;
 
-use crate::{TestError, TestSuiteResult};
+use self::channel::{AsyncReporter, ReporterEvent, SpawnedReporter};
+use crate::{TestError, TestId, TestSuiteId, TestSuiteResult};
 
-pub trait Reporter {
+/// Receives the events of the run.
+///
+/// All the events are ignored by default, so that a reporter only has to handle the ones
+/// it needs.
+pub trait Reporter: Send {
     fn name(&self) -> &'static str;
-    fn on_test_suite_creation_started(&mut self, name: &str);
-    fn on_test_suite_ignored(&mut self, name: &str);
-    fn on_test_suite_creation_finished(&mut self, name: &str, error: Option<&TestError>);
-    fn on_test_suite_start(&mut self, name: &str);
-    fn on_test_suite_end(&mut self, name: &str, result: &TestSuiteResult);
-    fn on_test_start(&mut self, name: &str);
-    fn on_test_ignored(&mut self, name: &str);
-    fn on_test_end(&mut self, name: &str, error: Option<&TestError>);
+
+    fn on_test_suite_creation_started(&mut self, id: &TestSuiteId) {
+        let _ = id;
+    }
+
+    fn on_test_suite_ignored(&mut self, id: &TestSuiteId) {
+        let _ = id;
+    }
+
+    fn on_test_suite_creation_finished(&mut self, id: &TestSuiteId, error: Option<&TestError>) {
+        let _ = (id, error);
+    }
+
+    fn on_test_suite_start(&mut self, id: &TestSuiteId) {
+        let _ = id;
+    }
+
+    fn on_test_suite_end(&mut self, id: &TestSuiteId, result: &TestSuiteResult) {
+        let _ = (id, result);
+    }
+
+    fn on_test_start(&mut self, id: &TestId) {
+        let _ = id;
+    }
+
+    fn on_test_ignored(&mut self, id: &TestId) {
+        let _ = id;
+    }
+
+    fn on_test_end(&mut self, id: &TestId, error: Option<&TestError>) {
+        let _ = (id, error);
+    }
+
+    /// Called once after all the test suites have been processed.
+    fn on_run_finished(&mut self, results: &[TestSuiteResult]) {
+        let _ = results;
+    }
 }
 
 impl fmt::Debug for dyn Reporter {
@@ -21,3 +58,141 @@ impl fmt::Debug for dyn Reporter {
         write!(f, "Reporter: {}", self.name())
     }
 }
+
+impl fmt::Debug for dyn AsyncReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AsyncReporter: {}", self.name())
+    }
+}
+
+/// Set of reporters used by the runner.
+///
+/// Synchronous reporters are invoked inline, while async reporters are spawned on their
+/// own tasks once the run starts.
+///
+/// Reporters are only `Send`; they are only accessed through `&mut self`, and the mutexes
+/// make the set `Sync`, so that the runner can be borrowed across `.await`s of a `Send`
+/// future.
+#[derive(Debug)]
+pub(crate) struct Reporters {
+    reporters: Mutex<Vec<Box<dyn Reporter>>>,
+    pending: Mutex<Vec<Box<dyn AsyncReporter>>>,
+    spawned: Vec<SpawnedReporter>,
+}
+
+impl Reporters {
+    pub(crate) fn new(reporters: Vec<Box<dyn Reporter>>) -> Self {
+        Self {
+            reporters: Mutex::new(reporters),
+            pending: Mutex::default(),
+            spawned: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporters().push(reporter);
+    }
+
+    pub(crate) fn add_async(&mut self, reporter: Box<dyn AsyncReporter>) {
+        self.pending.get_mut().unwrap().push(reporter);
+    }
+
+    /// Spawns async reporters. Must be called from within a Tokio runtime.
+    pub(crate) fn start(&mut self) {
+        self.spawned.extend(
+            self.pending
+                .get_mut()
+                .unwrap()
+                .drain(..)
+                .map(SpawnedReporter::spawn),
+        );
+    }
+
+    /// Waits for async reporters to process all the events.
+    pub(crate) async fn finish(&mut self) {
+        for reporter in self.spawned.drain(..) {
+            reporter.finish().await;
+        }
+    }
+
+    fn reporters(&mut self) -> &mut Vec<Box<dyn Reporter>> {
+        self.reporters.get_mut().unwrap()
+    }
+
+    fn send(&self, event: impl FnOnce() -> ReporterEvent) {
+        if self.spawned.is_empty() {
+            return;
+        }
+        let event = event();
+        for reporter in &self.spawned {
+            reporter.handle().send(event.clone());
+        }
+    }
+
+    pub(crate) fn on_test_suite_creation_started(&mut self, id: &TestSuiteId) {
+        for reporter in self.reporters() {
+            reporter.on_test_suite_creation_started(id);
+        }
+        self.send(|| ReporterEvent::TestSuiteCreationStarted(id.clone()));
+    }
+
+    pub(crate) fn on_test_suite_ignored(&mut self, id: &TestSuiteId) {
+        for reporter in self.reporters() {
+            reporter.on_test_suite_ignored(id);
+        }
+        self.send(|| ReporterEvent::TestSuiteIgnored(id.clone()));
+    }
+
+    pub(crate) fn on_test_suite_creation_finished(
+        &mut self,
+        id: &TestSuiteId,
+        error: Option<&TestError>,
+    ) {
+        for reporter in self.reporters() {
+            reporter.on_test_suite_creation_finished(id, error);
+        }
+        self.send(|| ReporterEvent::TestSuiteCreationFinished(id.clone(), error.cloned()));
+    }
+
+    pub(crate) fn on_test_suite_start(&mut self, id: &TestSuiteId) {
+        for reporter in self.reporters() {
+            reporter.on_test_suite_start(id);
+        }
+        self.send(|| ReporterEvent::TestSuiteStarted(id.clone()));
+    }
+
+    pub(crate) fn on_test_suite_end(&mut self, id: &TestSuiteId, result: &TestSuiteResult) {
+        for reporter in self.reporters() {
+            reporter.on_test_suite_end(id, result);
+        }
+        self.send(|| ReporterEvent::TestSuiteFinished(id.clone(), result.clone()));
+    }
+
+    pub(crate) fn on_test_start(&mut self, id: &TestId) {
+        for reporter in self.reporters() {
+            reporter.on_test_start(id);
+        }
+        self.send(|| ReporterEvent::TestStarted(id.clone()));
+    }
+
+    pub(crate) fn on_test_ignored(&mut self, id: &TestId) {
+        for reporter in self.reporters() {
+            reporter.on_test_ignored(id);
+        }
+        self.send(|| ReporterEvent::TestIgnored(id.clone()));
+    }
+
+    pub(crate) fn on_test_end(&mut self, id: &TestId, error: Option<&TestError>) {
+        for reporter in self.reporters() {
+            reporter.on_test_end(id, error);
+        }
+        self.send(|| ReporterEvent::TestFinished(id.clone(), error.cloned()));
+    }
+
+    pub(crate) fn on_run_finished(&mut self, results: &[TestSuiteResult]) {
+        for reporter in self.reporters() {
+            reporter.on_run_finished(results);
+        }
+        self.send(|| ReporterEvent::RunFinished(results.to_vec()));
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{Artifact, TestError, TestResult, TestSuiteId, TestSuiteResult, reporter::Reporter};
+
+/// Reporter that emits the results in the [TAP version 14](https://testanything.org/tap-version-14-specification.html)
+/// format.
+///
+/// Each test suite is represented as a subtest, so its test points are written once the
+/// suite is finished.
+pub struct TapReporter {
+    writer: Box<dyn Write + Send>,
+    /// Number of test points written at the top level.
+    suites_written: usize,
+    /// Whether writing has failed, so that the error is only reported once.
+    failed: bool,
+}
+
+impl std::fmt::Debug for TapReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TapReporter")
+            .field("suites_written", &self.suites_written)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TapReporter {
+    const INDENT: &'static str = "    ";
+
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        let mut this = Self {
+            writer: Box::new(writer),
+            suites_written: 0,
+            failed: false,
+        };
+        this.write_lines(&["TAP version 14".to_string()]);
+        this
+    }
+
+    /// Creates a reporter that writes to the file at the specified path.
+    pub fn to_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    fn write_lines(&mut self, lines: &[String]) {
+        if self.failed {
+            return;
+        }
+        let result = lines
+            .iter()
+            .try_for_each(|line| writeln!(self.writer, "{}", line))
+            .and_then(|()| self.writer.flush());
+        if let Err(err) = result {
+            tracing::error!("Failed to write TAP output: {}", err);
+            self.failed = true;
+        }
+    }
+
+    fn test_point(
+        lines: &mut Vec<String>,
+        indent: &str,
+        number: usize,
+        description: &str,
+        ok: bool,
+        skip: bool,
+    ) {
+        let status = if ok { "ok" } else { "not ok" };
+        let directive = if skip { " # SKIP" } else { "" };
+        lines.push(format!(
+            "{indent}{status} {number} - {}{directive}",
+            escape(description)
+        ));
+    }
+
+    /// Writes a YAML diagnostic block for the error and artifacts, if there are any.
+    fn diagnostic(
+        lines: &mut Vec<String>,
+        indent: &str,
+        error: Option<&TestError>,
+        artifacts: &[Artifact],
+    ) {
+        if error.is_none() && artifacts.is_empty() {
+            return;
+        }
+        lines.push(format!("{indent}  ---"));
+        if let Some(error) = error {
+            lines.push(format!("{indent}  phase: {}", error.phase()));
+            lines.push(format!("{indent}  message: |-"));
+            for line in format!("{:#}", error.inner()).lines() {
+                lines.push(format!("{indent}    {line}"));
+            }
+        }
+        if !artifacts.is_empty() {
+            lines.push(format!("{indent}  artifacts:"));
+            for artifact in artifacts {
+                lines.push(format!("{indent}    - name: {:?}", artifact.name));
+                lines.push(format!(
+                    "{indent}      path: {:?}",
+                    artifact.path.display().to_string()
+                ));
+            }
+        }
+        lines.push(format!("{indent}  ..."));
+    }
+
+    fn subtest(lines: &mut Vec<String>, result: &TestSuiteResult) {
+        let indent = Self::INDENT;
+        lines.push(format!("# Subtest: {}", result.name));
+        for (idx, test) in result.tests.iter().enumerate() {
+            Self::test_result(lines, indent, idx + 1, test);
+        }
+        let mut plan = result.tests.len();
+        if let Some(error) = &result.error {
+            // Suite-level failures are reported as an additional test point, so that
+            // they are visible within the subtest as well.
+            plan += 1;
+            Self::test_point(lines, indent, plan, error.phase(), false, false);
+            Self::diagnostic(lines, indent, Some(error), &result.artifacts);
+        }
+        lines.push(format!("{indent}1..{plan}"));
+    }
+
+    fn test_result(lines: &mut Vec<String>, indent: &str, number: usize, test: &TestResult) {
+        Self::test_point(
+            lines,
+            indent,
+            number,
+            &test.name,
+            test.passed(),
+            test.ignored,
+        );
+        Self::diagnostic(lines, indent, test.error.as_ref(), &test.artifacts);
+    }
+}
+
+/// Escapes characters that have a special meaning in the test point description.
+fn escape(description: &str) -> String {
+    description.replace('\\', r"\\").replace('#', r"\#")
+}
+
+impl Reporter for TapReporter {
+    fn name(&self) -> &'static str {
+        "TapReporter"
+    }
+
+    fn on_test_suite_ignored(&mut self, id: &TestSuiteId) {
+        self.suites_written += 1;
+        let mut lines = Vec::new();
+        Self::test_point(
+            &mut lines,
+            "",
+            self.suites_written,
+            &id.to_string(),
+            true,
+            true,
+        );
+        self.write_lines(&lines);
+    }
+
+    fn on_test_suite_end(&mut self, _id: &TestSuiteId, result: &TestSuiteResult) {
+        self.suites_written += 1;
+        let mut lines = Vec::new();
+        Self::subtest(&mut lines, result);
+        Self::test_point(
+            &mut lines,
+            "",
+            self.suites_written,
+            &result.name,
+            result.passed,
+            false,
+        );
+        self.write_lines(&lines);
+    }
+
+    fn on_run_finished(&mut self, _results: &[TestSuiteResult]) {
+        let plan = format!("1..{}", self.suites_written);
+        self.write_lines(&[plan]);
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+use futures::future::BoxFuture;
+
+use crate::check;
+
+/// Resource shared by suites, e.g. a spawned server or a seeded database.
+///
+/// A suite declares resources by taking `Arc<R>` parameters in its constructor after the
+/// config. The resource is created once, when the first suite that declares it is created,
+/// and torn down after the last suite that declares it finishes.
+///
+/// ```ignore
+/// struct Database { /* ... */ }
+///
+/// #[async_trait::async_trait]
+/// impl Resource<TestConfig> for Database {
+///     async fn create(config: &TestConfig) -> anyhow::Result<Self> { /* ... */ }
+/// }
+///
+/// #[test_suite("Users")]
+/// impl UsersFlow {
+///     #[constructor]
+///     async fn new(c: &TestConfig, db: Arc<Database>) -> anyhow::Result<Self> { /* ... */ }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait Resource<C>: Sized + Send + Sync + 'static {
+    async fn create(config: &C) -> anyhow::Result<Self>;
+
+    async fn teardown(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+type SharedValue = Arc<dyn Any + Send + Sync>;
+
+/// Declaration of a resource required by a suite.
+pub struct ResourceDecl<C> {
+    id: TypeId,
+    name: &'static str,
+    create: for<'a> fn(&'a C) -> BoxFuture<'a, anyhow::Result<SharedValue>>,
+    teardown: fn(SharedValue) -> BoxFuture<'static, anyhow::Result<()>>,
+}
+
+impl<C: Sync + 'static> ResourceDecl<C> {
+    pub fn of<R: Resource<C>>() -> Self {
+        Self {
+            id: TypeId::of::<R>(),
+            name: std::any::type_name::<R>(),
+            create: create_erased::<C, R>,
+            teardown: teardown_erased::<C, R>,
+        }
+    }
+}
+
+impl<C> fmt::Debug for ResourceDecl<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn create_erased<C: Sync, R: Resource<C>>(
+    config: &C,
+) -> BoxFuture<'_, anyhow::Result<SharedValue>> {
+    Box::pin(async move { Ok(Arc::new(R::create(config).await?) as SharedValue) })
+}
+
+fn teardown_erased<C, R: Resource<C>>(
+    value: SharedValue,
+) -> BoxFuture<'static, anyhow::Result<()>> {
+    Box::pin(async move {
+        let value = value
+            .downcast::<R>()
+            .unwrap_or_else(|_| unreachable!("Resource is stored under its type id"));
+        value.teardown().await
+    })
+}
+
+/// Resources available to a suite constructor.
+#[derive(Debug, Default)]
+pub struct Resources {
+    values: HashMap<TypeId, SharedValue>,
+}
+
+impl Resources {
+    /// Returns the resource, which must be declared by the suite.
+    pub fn get<R: Send + Sync + 'static>(&self) -> anyhow::Result<Arc<R>> {
+        let value = self
+            .values
+            .get(&TypeId::of::<R>())
+            .cloned()
+            .ok_or_else(|| {
+                anyhow::format_err!(
+                    "Resource {} is not declared by the suite",
+                    std::any::type_name::<R>()
+                )
+            })?;
+        Ok(value
+            .downcast::<R>()
+            .unwrap_or_else(|_| unreachable!("Resource is stored under its type id")))
+    }
+}
+
+#[derive(Debug)]
+struct Entry<C> {
+    decl: ResourceDecl<C>,
+    value: Option<SharedValue>,
+    /// Error of a failed creation, so that it isn't retried by every suite.
+    error: Option<String>,
+    /// Number of suites that declare the resource and haven't finished yet.
+    users: usize,
+}
+
+/// Resources of the run, created lazily and torn down once they're no longer needed.
+#[derive(Debug)]
+pub(crate) struct ResourceRegistry<C> {
+    entries: HashMap<TypeId, Entry<C>>,
+}
+
+impl<C: Sync + 'static> ResourceRegistry<C> {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Registers a suite that will be run.
+    pub(crate) fn add_user(&mut self, decls: Vec<ResourceDecl<C>>) {
+        for decl in decls {
+            self.entries
+                .entry(decl.id)
+                .or_insert_with(|| Entry {
+                    decl,
+                    value: None,
+                    error: None,
+                    users: 0,
+                })
+                .users += 1;
+        }
+    }
+
+    /// Returns the declared resources, creating the ones that don't exist yet.
+    pub(crate) async fn acquire(
+        &mut self,
+        decls: &[ResourceDecl<C>],
+        config: &C,
+    ) -> anyhow::Result<Resources> {
+        let mut resources = Resources::default();
+        for decl in decls {
+            let entry = self
+                .entries
+                .get_mut(&decl.id)
+                .expect("Resource users must be registered");
+            if let Some(err) = &entry.error {
+                anyhow::bail!("Failed to create resource {}: {err}", entry.decl.name);
+            }
+            let value = match &entry.value {
+                Some(value) => value.clone(),
+                None => {
+                    let value = check::run((entry.decl.create)(config))
+                        .await
+                        .inspect_err(|err| entry.error = Some(format!("{err:#}")))
+                        .map_err(|err| {
+                            err.context(format!("Failed to create resource {}", entry.decl.name))
+                        })?;
+                    entry.value.insert(value).clone()
+                }
+            };
+            resources.values.insert(decl.id, value);
+        }
+        Ok(resources)
+    }
+
+    /// Unregisters a finished suite, returning the resources that are no longer used and
+    /// must be torn down.
+    pub(crate) fn release(&mut self, decls: &[ResourceDecl<C>]) -> Vec<ResourceTeardown> {
+        let mut teardowns = Vec::new();
+        for decl in decls {
+            let Some(entry) = self.entries.get_mut(&decl.id) else {
+                continue;
+            };
+            entry.users = entry.users.saturating_sub(1);
+            if entry.users == 0 {
+                let entry = self.entries.remove(&decl.id).unwrap();
+                teardowns.extend(ResourceTeardown::new(entry));
+            }
+        }
+        teardowns
+    }
+
+    /// Returns all the created resources, e.g. if the run was stopped early.
+    pub(crate) fn release_all(&mut self) -> Vec<ResourceTeardown> {
+        self.entries
+            .drain()
+            .filter_map(|(_, entry)| ResourceTeardown::new(entry))
+            .collect()
+    }
+}
+
+/// Pending teardown of a resource.
+pub(crate) struct ResourceTeardown {
+    name: &'static str,
+    future: BoxFuture<'static, anyhow::Result<()>>,
+}
+
+impl ResourceTeardown {
+    fn new<C>(entry: Entry<C>) -> Option<Self> {
+        let value = entry.value?;
+        Some(Self {
+            name: entry.decl.name,
+            future: (entry.decl.teardown)(value),
+        })
+    }
+
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let name = self.name;
+        check::run(self.future)
+            .await
+            .map_err(|err| err.context(format!("Failed to tear down resource {name}")))
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    future::Future,
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use tokio::task::{AbortHandle, JoinSet};
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument as _;
+
+use crate::{artifacts::ArtifactStore, logs, temp_dir::TempDir};
+
+tokio::task_local! {
+    static CURRENT_SCOPE: Scope;
+}
+
+/// Cancellation token, background tasks, artifacts and temporary directory owned by a
+/// suite or a test.
+#[derive(Debug, Clone)]
+pub(crate) struct Scope {
+    token: CancellationToken,
+    tasks: Arc<Mutex<JoinSet<()>>>,
+    artifacts: Arc<ArtifactStore>,
+    temp_dir: Arc<TempDir>,
+    /// Deadline of the test body along with the timeout it was computed from, set once
+    /// the test body starts.
+    deadline: Arc<OnceLock<(Instant, Duration)>>,
+    /// Soft assertions failed since the end of the last hook or test body.
+    failed_checks: Arc<Mutex<Vec<String>>>,
+}
+
+impl Scope {
+    /// Creates a scope nested into the current one, or into `root` if there's none.
+    pub(crate) fn nested(
+        root: &CancellationToken,
+        artifacts_dir: PathBuf,
+        temp_dir: PathBuf,
+    ) -> Self {
+        let token = CURRENT_SCOPE
+            .try_with(|scope| scope.token.child_token())
+            .unwrap_or_else(|_| root.child_token());
+        Self {
+            token,
+            tasks: Arc::default(),
+            artifacts: Arc::new(ArtifactStore::new(artifacts_dir)),
+            temp_dir: Arc::new(TempDir::new(temp_dir)),
+            deadline: Arc::default(),
+            failed_checks: Arc::default(),
+        }
+    }
+
+    pub(crate) fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub(crate) fn artifacts(&self) -> &Arc<ArtifactStore> {
+        &self.artifacts
+    }
+
+    pub(crate) fn temp_dir(&self) -> &Arc<TempDir> {
+        &self.temp_dir
+    }
+
+    pub(crate) fn deadline(&self) -> &Arc<OnceLock<(Instant, Duration)>> {
+        &self.deadline
+    }
+
+    pub(crate) fn record_failed_check(&self, message: String) {
+        self.failed_checks.lock().unwrap().push(message);
+    }
+
+    pub(crate) fn take_failed_checks(&self) -> Vec<String> {
+        std::mem::take(&mut *self.failed_checks.lock().unwrap())
+    }
+
+    /// Runs the future with this scope being the current one.
+    pub(crate) async fn run<F: Future>(&self, future: F) -> F::Output {
+        CURRENT_SCOPE.scope(self.clone(), future).await
+    }
+
+    /// Cancels the scope and waits for the spawned tasks to finish, aborting the ones
+    /// that are still running after `timeout`.
+    ///
+    /// Returns the number of aborted tasks.
+    pub(crate) async fn shutdown(&self, timeout: Duration) -> usize {
+        self.token.cancel();
+        let deadline = Instant::now() + timeout;
+        let mut aborted = 0;
+        loop {
+            // Tasks may spawn other tasks while we're waiting, so drain until empty.
+            let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+            if tasks.is_empty() {
+                return aborted;
+            }
+            let drained = tokio::time::timeout_at(deadline.into(), async {
+                while tasks.join_next().await.is_some() {}
+            })
+            .await;
+            if drained.is_err() {
+                aborted += tasks.len();
+                tasks.shutdown().await;
+            }
+        }
+    }
+}
+
+/// Returns the cancellation token of the current test, or of the current suite when
+/// called from the constructor or `before_all`/`after_all` hooks.
+///
+/// The token is cancelled once the test finishes (including when it times out) or the
+/// run is interrupted, so that background work can be stopped cooperatively.
+///
+/// # Panics
+///
+/// Panics if called outside of a test suite.
+pub fn cancellation_token() -> CancellationToken {
+    current("cancellation_token", |scope| scope.token.clone())
+}
+
+/// Spawns a task owned by the current test, or by the current suite when called from
+/// the constructor or `before_all`/`after_all` hooks.
+///
+/// Once the owner finishes, its [`cancellation_token`] is cancelled and the runner waits
+/// for its tasks to finish for the configured time, aborting them afterwards. Logs of the
+/// task are captured along with the logs of its owner while the owner is running.
+///
+/// # Panics
+///
+/// Panics if called outside of a test suite.
+pub fn spawn<F>(future: F) -> AbortHandle
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    current("spawn", |scope| {
+        // Spawned tasks belong to the same scope, so that they can spawn tasks too.
+        let task = CURRENT_SCOPE.scope(scope.clone(), logs::inherit(future.in_current_span()));
+        scope.tasks.lock().unwrap().spawn(task)
+    })
+}
+
+/// Invokes `f` with the current scope, panicking if there's none.
+pub(crate) fn current<R>(function: &str, f: impl FnOnce(&Scope) -> R) -> R {
+    try_current(f).unwrap_or_else(|| panic!("`e2e::{function}` must be called from a test suite"))
+}
+
+/// Invokes `f` with the current scope, if any.
+pub(crate) fn try_current<R>(f: impl FnOnce(&Scope) -> R) -> Option<R> {
+    CURRENT_SCOPE.try_with(f).ok()
+}
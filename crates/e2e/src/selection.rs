@@ -0,0 +1,46 @@
+use std::collections::HashSet;
+
+use crate::{RunRecord, TestId, TestStatus, TestSuiteId};
+
+/// Tests that failed in a previous run.
+///
+/// If a suite failed outside of its tests (e.g. in the constructor or in a hook), or
+/// failed without any failed test, the whole suite is selected.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FailedTests {
+    whole_suites: HashSet<TestSuiteId>,
+    suites: HashSet<TestSuiteId>,
+    tests: HashSet<TestId>,
+}
+
+impl FailedTests {
+    pub(crate) fn from_run(run: &RunRecord) -> Self {
+        let mut this = Self::default();
+        for suite in run.suites.iter().filter(|suite| !suite.passed) {
+            let failed_tests: Vec<_> = suite
+                .tests
+                .iter()
+                .filter(|test| matches!(test.status, TestStatus::Failed | TestStatus::Cancelled))
+                .collect();
+            if suite.error_phase.is_some() || failed_tests.is_empty() {
+                this.whole_suites.insert(suite.id.clone());
+            }
+            this.suites.insert(suite.id.clone());
+            this.tests
+                .extend(failed_tests.into_iter().map(|test| test.id.clone()));
+        }
+        this
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.suites.is_empty()
+    }
+
+    pub(crate) fn includes_suite(&self, id: &TestSuiteId) -> bool {
+        self.suites.contains(id)
+    }
+
+    pub(crate) fn includes_test(&self, id: &TestId) -> bool {
+        self.whole_suites.contains(&id.suite_id()) || self.tests.contains(id)
+    }
+}
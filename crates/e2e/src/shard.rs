@@ -0,0 +1,125 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::Context as _;
+
+use crate::{RunRecord, TestSuiteId};
+
+/// Part of the run executed by a single runner when suites are split across several
+/// machines, written as `K/N` (e.g. `2/4` is the second of four shards).
+///
+/// Suites are assigned to shards deterministically, so each suite is executed by exactly
+/// one of the runners as long as they all have the same set of suites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// 1-based index of the shard.
+    index: usize,
+    total: usize,
+}
+
+impl Shard {
+    pub fn new(index: usize, total: usize) -> anyhow::Result<Self> {
+        anyhow::ensure!(total > 0, "Number of shards must be positive");
+        anyhow::ensure!(
+            (1..=total).contains(&index),
+            "Shard index must be in range 1..={total}, got {index}"
+        );
+        Ok(Self { index, total })
+    }
+
+    /// Selects suites that belong to this shard.
+    ///
+    /// Without durations, suites are assigned by a stable hash of their identifiers.
+    /// With durations from a previous run, suites are distributed so that shards take
+    /// roughly the same time; suites missing from the previous run are assumed to take
+    /// the average time.
+    pub(crate) fn select(
+        &self,
+        suites: &[TestSuiteId],
+        durations: Option<&RunRecord>,
+    ) -> HashSet<TestSuiteId> {
+        let Some(durations) = durations else {
+            return suites
+                .iter()
+                .filter(|id| {
+                    stable_hash(&id.to_string()) % self.total as u64 == self.index as u64 - 1
+                })
+                .cloned()
+                .collect();
+        };
+
+        let known: HashMap<&TestSuiteId, u64> = durations
+            .suites
+            .iter()
+            .map(|suite| (&suite.id, suite.duration_ms))
+            .collect();
+        let average = if known.is_empty() {
+            1
+        } else {
+            known.values().sum::<u64>() / known.len() as u64
+        };
+
+        // Longest processing time first: each suite goes to the least loaded shard.
+        let mut ordered: Vec<(u64, &TestSuiteId)> = suites
+            .iter()
+            .map(|id| (known.get(id).copied().unwrap_or(average), id))
+            .collect();
+        ordered.sort_by(|(lhs_ms, lhs_id), (rhs_ms, rhs_id)| {
+            rhs_ms.cmp(lhs_ms).then_with(|| lhs_id.cmp(rhs_id))
+        });
+        let mut loads = vec![0_u64; self.total];
+        let mut selected = HashSet::new();
+        for (duration_ms, id) in ordered {
+            let (shard, _) = loads
+                .iter()
+                .enumerate()
+                .min_by_key(|(shard, load)| (**load, *shard))
+                .expect("There is at least one shard");
+            loads[shard] += duration_ms;
+            if shard == self.index - 1 {
+                selected.insert(id.clone());
+            }
+        }
+        selected
+    }
+}
+
+impl FromStr for Shard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, total) = s
+            .split_once('/')
+            .context("Shard must be specified as K/N, e.g. 1/4")?;
+        let index = index.trim().parse().context("Invalid shard index")?;
+        let total = total.trim().parse().context("Invalid number of shards")?;
+        Self::new(index, total)
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.total)
+    }
+}
+
+/// Loads a run record used to balance shards.
+pub(crate) fn load_durations(path: &Path) -> anyhow::Result<RunRecord> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// FNV-1a hash, which unlike `std` hashers is guaranteed to be stable across builds
+/// and platforms.
+pub(crate) fn stable_hash(s: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    s.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
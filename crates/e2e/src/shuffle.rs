@@ -0,0 +1,47 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::shard::stable_hash;
+
+/// Small deterministic PRNG (SplitMix64): shuffled order must be reproducible from the
+/// seed regardless of the platform or the version of the dependencies.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator for a specific scope (e.g. a test suite), so that the order
+    /// within the scope doesn't depend on what was shuffled before it.
+    pub(crate) fn scoped(seed: u64, scope: &str) -> Self {
+        Self::new(seed ^ stable_hash(scope))
+    }
+
+    /// Generates a seed for runs where it was not provided explicitly.
+    pub(crate) fn random_seed() -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        Self::new(nanos ^ u64::from(std::process::id())).next_u64()
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle.
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
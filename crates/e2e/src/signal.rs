@@ -0,0 +1,159 @@
+use std::{
+    sync::{
+        Mutex, Once,
+        atomic::{AtomicU64, Ordering},
+        mpsc,
+    },
+    time::Duration,
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::process;
+
+/// Offset of the exit code used when the process is terminated by a signal, to which the
+/// signal number is added, like shells do.
+const SIGNAL_EXIT_CODE_BASE: i32 = 128;
+
+/// Time given to the processes of services to exit once killed, before exiting.
+const KILL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Runs in progress, cancelled on the first SIGINT/SIGTERM.
+static RUNS: Mutex<Vec<(u64, CancellationToken)>> = Mutex::new(Vec::new());
+
+/// Cancels the run on the first SIGINT/SIGTERM, and terminates the process on the second
+/// one, until the returned guard is dropped.
+///
+/// Signal listeners can't be removed once installed, so they are installed once for the
+/// whole process, on a dedicated thread that outlives the runtime of the run. Signals
+/// received while no run is in progress terminate the process, like they would by default.
+pub(crate) fn handle_signals(token: CancellationToken) -> SignalGuard {
+    static LISTENER: Once = Once::new();
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    LISTENER.call_once(|| {
+        let (installed_tx, installed_rx) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("e2e-signals".to_string())
+            .spawn(move || listen(installed_tx));
+        // Wait for the listeners, so that signals are not missed at the start of the run.
+        let installed = match spawned {
+            Ok(_) => installed_rx
+                .recv()
+                .unwrap_or_else(|_| Err(std::io::Error::other("Signal listener thread exited"))),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = installed {
+            tracing::warn!("Failed to listen for interrupt signals: {}", err);
+        }
+    });
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    RUNS.lock().unwrap().push((id, token));
+    SignalGuard { id }
+}
+
+/// Stops cancelling the run on signals once dropped.
+#[derive(Debug)]
+pub(crate) struct SignalGuard {
+    id: u64,
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        RUNS.lock().unwrap().retain(|(id, _)| *id != self.id);
+    }
+}
+
+fn listen(installed: mpsc::Sender<std::io::Result<()>>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            let _ = installed.send(Err(err));
+            return;
+        }
+    };
+    runtime.block_on(async {
+        let mut signals = match Signals::new() {
+            Ok(signals) => signals,
+            Err(err) => {
+                let _ = installed.send(Err(err));
+                return;
+            }
+        };
+        let _ = installed.send(Ok(()));
+        loop {
+            let signal = signals.recv().await;
+            let runs: Vec<_> = RUNS
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, token)| token.clone())
+                .collect();
+            if runs.is_empty() {
+                exit(signal).await;
+            }
+            if runs.iter().all(CancellationToken::is_cancelled) {
+                eprintln!("Interrupted again: exiting");
+                exit(signal).await;
+            }
+            eprintln!("Interrupted: cancelling the run, send the signal again to exit immediately");
+            runs.iter().for_each(CancellationToken::cancel);
+        }
+    });
+}
+
+/// Exits without unwinding, killing the processes of services first, as they are not
+/// killed on drop then.
+async fn exit(signal: i32) -> ! {
+    process::kill_all(KILL_TIMEOUT).await;
+    std::process::exit(SIGNAL_EXIT_CODE_BASE + signal)
+}
+
+#[cfg(unix)]
+struct Signals {
+    interrupt: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    /// Waits for a signal, returning its number.
+    async fn recv(&mut self) -> i32 {
+        tokio::select! {
+            _ = self.interrupt.recv() => libc::SIGINT,
+            _ = self.terminate.recv() => libc::SIGTERM,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self)
+    }
+
+    /// Waits for Ctrl-C, returning the number of the matching SIGINT.
+    async fn recv(&mut self) -> i32 {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::warn!("Failed to listen for interrupt signals: {}", err);
+            std::future::pending::<()>().await;
+        }
+        2
+    }
+}
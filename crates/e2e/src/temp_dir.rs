@@ -0,0 +1,76 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::scope;
+
+/// Temporary directory of a suite or a test, created on the first use.
+#[derive(Debug)]
+pub(crate) struct TempDir {
+    path: PathBuf,
+    created: AtomicBool,
+}
+
+impl TempDir {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            created: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn get(&self) -> io::Result<&Path> {
+        if !self.created.load(Ordering::Acquire) {
+            fs::create_dir_all(&self.path)?;
+            self.created.store(true, Ordering::Release);
+        }
+        Ok(&self.path)
+    }
+
+    /// Removes the directory unless it must be kept, returning the path of the kept one.
+    pub(crate) fn finish(&self, keep: bool) -> Option<PathBuf> {
+        if !self.created.load(Ordering::Acquire) {
+            return None;
+        }
+        if keep {
+            return Some(self.path.clone());
+        }
+        if let Err(err) = fs::remove_dir_all(&self.path) {
+            tracing::warn!("Failed to remove {}: {}", self.path.display(), err);
+        }
+        None
+    }
+}
+
+/// Removes empty directories under `path` (including itself), e.g. parents of removed
+/// temporary directories.
+pub(crate) fn remove_empty_dirs(path: &Path) {
+    let Ok(entries) = fs::read_dir(path) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_type().is_ok_and(|ty| ty.is_dir()) {
+            remove_empty_dirs(&entry.path());
+        }
+    }
+    // Fails if the directory isn't empty, which is expected.
+    let _ = fs::remove_dir(path);
+}
+
+/// Returns the temporary directory of the current test, or of the current suite when
+/// called from the constructor or `before_all`/`after_all` hooks, creating it on the
+/// first call.
+///
+/// The directory is removed once its owner finishes successfully, and kept (with the path
+/// reported) if it fails or `--keep-temp` is specified.
+///
+/// # Panics
+///
+/// Panics if called outside of a test suite.
+pub fn temp_dir() -> io::Result<PathBuf> {
+    scope::current("temp_dir", |scope| {
+        scope.temp_dir().get().map(Path::to_path_buf)
+    })
+}
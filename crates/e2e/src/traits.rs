@@ -1,11 +1,33 @@
 use std::fmt;
 
+use crate::{ResourceDecl, Resources, TestContext, TestMetadata, TestSuiteId};
+
 #[async_trait::async_trait]
 pub trait TestSuiteFactory<C>: Send + Sync + 'static {
-    fn name(&self) -> String;
+    fn id(&self) -> TestSuiteId;
+
+    fn name(&self) -> String {
+        self.id().to_string()
+    }
+
+    /// Returns static information about the tests of the suite.
+    ///
+    /// Unlike [`TestSuiteFactory::create_suite`], this method must not perform any setup.
+    fn tests_metadata(&self) -> Vec<TestMetadata>;
+
+    /// Returns the shared resources the suite requires, see [`Resource`](crate::Resource).
+    fn resources(&self) -> Vec<ResourceDecl<C>> {
+        Vec::new()
+    }
 
     /// Creates a new test suite instance.
-    async fn create_suite(&self, config: &C) -> anyhow::Result<Box<dyn TestSuite>>;
+    ///
+    /// `resources` contains all the resources declared by [`TestSuiteFactory::resources`].
+    async fn create_suite(
+        &self,
+        config: &C,
+        resources: &Resources,
+    ) -> anyhow::Result<Box<dyn TestSuite>>;
 }
 
 impl<C: std::fmt::Debug + 'static> fmt::Debug for Box<dyn TestSuiteFactory<C>> {
@@ -14,6 +36,27 @@ impl<C: std::fmt::Debug + 'static> fmt::Debug for Box<dyn TestSuiteFactory<C>> {
     }
 }
 
+/// Hooks that are run once per run, around all the test suites.
+///
+/// `after_all` is invoked even if `before_all` or any of the suites failed, so it can be
+/// used to tear down resources shared by the whole run (e.g. a local database process).
+#[async_trait::async_trait]
+pub trait RunHooks<C>: Send + Sync + 'static {
+    async fn before_all(&self, _config: &C) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn after_all(&self, _config: &C) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl<C> fmt::Debug for dyn RunHooks<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RunHooks")
+    }
+}
+
 #[async_trait::async_trait]
 pub trait TestSuite: Send + Sync + 'static {
     fn tests(&self) -> Vec<Box<dyn Test>>;
@@ -38,7 +81,11 @@ pub trait TestSuite: Send + Sync + 'static {
 #[async_trait::async_trait]
 pub trait Test: Send + Sync + 'static {
     fn name(&self) -> String;
-    async fn run(&self) -> anyhow::Result<()>;
+    /// Runs the test.
+    ///
+    /// The test fails if it doesn't finish by [`TestContext::deadline`]. It is then given
+    /// the teardown timeout to release its resources (e.g. fixtures) before being dropped.
+    async fn run(&self, ctx: &TestContext) -> anyhow::Result<()>;
     fn ignore(&self) -> bool {
         false
     }
@@ -46,6 +93,10 @@ pub trait Test: Send + Sync + 'static {
     fn only(&self) -> bool {
         false
     }
+
+    fn tags(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 impl fmt::Debug for dyn Test {
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use e2e::{HtmlReporter, TestContext, TestRunnerConfiguration, test_suite};
+
+#[derive(Debug, Clone)]
+struct TestConfig;
+
+#[derive(Debug, Clone)]
+struct TestFlow;
+
+#[test_suite("Artifacts suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        e2e::attach_text("setup.log", "suite created")?;
+        // Must not collide with the directory of the test.
+        e2e::attach_text("Failing", "suite artifact")?;
+        Ok(Self)
+    }
+
+    #[before_each]
+    async fn before_each(&self) -> anyhow::Result<()> {
+        e2e::attach_bytes("before_each.bin", [1, 2, 3])?;
+        Ok(())
+    }
+
+    #[test_case("Failing")]
+    async fn failing(&self, ctx: &TestContext) -> anyhow::Result<()> {
+        let dump = ctx.temp_dir()?.join("dump.sql");
+        std::fs::write(&dump, "SELECT 1;")?;
+        ctx.attach_file("db/dump.sql", &dump)?;
+        ctx.attach_text("http.txt", "GET / HTTP/1.1")?;
+        anyhow::bail!("Expected failure")
+    }
+
+    #[test_case("..")]
+    async fn parent(&self) -> anyhow::Result<()> {
+        e2e::attach_text("..", "escaped")?;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn artifacts_are_stored_per_run_and_linked_from_reports() {
+    let dir = std::env::temp_dir().join(format!("e2e-artifacts-test-{}", std::process::id()));
+    let report = dir.join("report.html");
+    let mut tester = e2e::TestRunner::new(TestConfig)
+        .with_runner_config(TestRunnerConfiguration::default().with_artifacts_dir(&dir))
+        .with_reporter(Box::new(HtmlReporter::new(&report)));
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+
+    let run_dirs: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    assert_eq!(run_dirs.len(), 1);
+    let suite_dir = run_dirs[0].join("Artifacts_suite");
+    let read = |path: PathBuf| std::fs::read(path).unwrap();
+    assert_eq!(
+        read(suite_dir.join("@artifacts/setup.log")),
+        b"suite created"
+    );
+    assert_eq!(read(suite_dir.join("Failing/before_each.bin")), [1, 2, 3]);
+    assert_eq!(read(suite_dir.join("Failing/db_dump.sql")), b"SELECT 1;");
+    assert_eq!(read(suite_dir.join("Failing/http.txt")), b"GET / HTTP/1.1");
+    assert_eq!(
+        read(suite_dir.join("@artifacts/Failing")),
+        b"suite artifact"
+    );
+    assert_eq!(read(suite_dir.join("__/__")), b"escaped");
+
+    let html = std::fs::read_to_string(&report).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let run_id = run_dirs[0].file_name().unwrap().to_str().unwrap();
+    assert!(
+        html.contains(&format!(
+            "<a href=\"{run_id}/Artifacts_suite/Failing/http.txt\">http.txt</a>"
+        )),
+        "{html}"
+    );
+    assert!(html.contains(&format!(
+        "<a href=\"{run_id}/Artifacts_suite/%40artifacts/setup.log\">setup.log</a>"
+    )));
+}
+
+#[tokio::test]
+async fn artifact_links_are_percent_encoded() {
+    let dir = std::env::temp_dir().join(format!("e2e artifacts #{}", std::process::id()));
+    // Not an ancestor of the artifacts directory, so that links are absolute.
+    let report_dir =
+        std::env::temp_dir().join(format!("e2e-artifacts-report-{}", std::process::id()));
+    std::fs::create_dir_all(&report_dir).unwrap();
+    let report = report_dir.join("report.html");
+    let mut tester = e2e::TestRunner::new(TestConfig)
+        .with_runner_config(TestRunnerConfiguration::default().with_artifacts_dir(&dir))
+        .with_reporter(Box::new(HtmlReporter::new(&report)));
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+
+    let html = std::fs::read_to_string(&report).unwrap();
+    std::fs::remove_dir_all(&report_dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let encoded_dir = format!("e2e%20artifacts%20%23{}", std::process::id());
+    let link = html
+        .lines()
+        .find(|line| line.contains(">setup.log</a>"))
+        .expect(&html);
+    assert!(
+        link.starts_with("<li><a href=\"file:///")
+            && link.contains(&format!("/{encoded_dir}/"))
+            && link.ends_with("/Artifacts_suite/%40artifacts/setup.log\">setup.log</a></li>"),
+        "{link}"
+    );
+}
@@ -0,0 +1,241 @@
+mod common;
+
+use std::time::Duration;
+
+use e2e::{TestRunnerConfiguration, TestSuiteResult, test_suite};
+
+use self::common::{ResultsReporter, TestConfig};
+
+#[derive(Debug, Clone)]
+struct InterruptedFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Interrupted suite")]
+impl InterruptedFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        c.log("create interrupted");
+        Ok(Self { config: c.clone() })
+    }
+
+    #[after_each]
+    async fn after_each(&self) -> anyhow::Result<()> {
+        self.config.log("after_each");
+        Ok(())
+    }
+
+    #[after_all]
+    async fn after_all(&self) -> anyhow::Result<()> {
+        self.config.log("after_all");
+        Ok(())
+    }
+
+    #[test_case("Slow")]
+    async fn slow(&self) -> anyhow::Result<()> {
+        self.config.log("slow");
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+
+    #[test_case("Next")]
+    async fn next(&self) -> anyhow::Result<()> {
+        self.config.log("next");
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingFlow;
+
+#[test_suite("Pending suite")]
+impl PendingFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        c.log("create pending");
+        Ok(Self)
+    }
+
+    #[test_case("Pending")]
+    async fn pending(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HangingTeardownFlow;
+
+#[test_suite("Hanging teardown suite")]
+impl HangingTeardownFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[after_all]
+    async fn after_all(&self) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+
+    #[test_case("Slow")]
+    async fn slow(&self) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InterruptedFixtureFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Interrupted fixture suite")]
+impl InterruptedFixtureFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[fixture(teardown = disconnect)]
+    async fn connection(&self) -> anyhow::Result<u32> {
+        self.config.log("connect");
+        Ok(1)
+    }
+
+    async fn disconnect(&self, connection: u32) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        self.config.log(format!("disconnect {connection}"));
+        Ok(())
+    }
+
+    #[test_case("Slow")]
+    async fn slow(&self, _connection: &u32) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+}
+
+/// Runs the tester, cancelling it once the first test had time to start.
+async fn run_interrupted(
+    tester: e2e::TestRunner<TestConfig>,
+) -> (Vec<TestSuiteResult>, anyhow::Result<()>) {
+    let (reporter, results) = ResultsReporter::new();
+    let tester = tester.with_reporter(Box::new(reporter));
+    let token = tester.cancellation_token();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        token.cancel();
+    });
+    let result = tester.run().await;
+    let results = results.lock().unwrap().clone();
+    (results, result)
+}
+
+#[tokio::test]
+async fn cancellation_runs_teardown_and_marks_remaining_tests() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone());
+    tester.add_suite(InterruptedFlow::new());
+    tester.add_suite(PendingFlow::new());
+    let (results, result) = run_interrupted(tester).await;
+
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("cancelled"), "{err}");
+    assert_eq!(
+        config.events.get(),
+        ["create interrupted", "slow", "after_each", "after_all"]
+    );
+
+    let tests: Vec<_> = results
+        .iter()
+        .flat_map(|suite| &suite.tests)
+        .map(|test| (test.id.to_string(), test.cancelled()))
+        .collect();
+    assert_eq!(
+        tests,
+        [
+            ("Interrupted suite / Slow".to_string(), true),
+            ("Interrupted suite / Next".to_string(), true),
+            ("Pending suite / Pending".to_string(), true),
+        ]
+    );
+    assert!(results.iter().all(|suite| !suite.passed));
+}
+
+#[tokio::test]
+async fn cancellation_tears_down_fixtures() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone());
+    tester.add_suite(InterruptedFixtureFlow::new());
+    let (results, result) = run_interrupted(tester).await;
+
+    assert!(result.is_err());
+    assert!(results[0].tests[0].cancelled());
+    assert_eq!(config.events.get(), ["connect", "disconnect 1"]);
+}
+
+#[tokio::test]
+async fn cancellation_bounds_teardown_by_grace_period() {
+    let mut tester = e2e::TestRunner::new(TestConfig::default()).with_runner_config(
+        TestRunnerConfiguration::default()
+            .with_cancellation_grace_period(Duration::from_millis(100)),
+    );
+    tester.add_suite(HangingTeardownFlow::new());
+    let (results, result) = tokio::time::timeout(Duration::from_secs(5), run_interrupted(tester))
+        .await
+        .expect("Teardown must be interrupted after the grace period");
+
+    assert!(result.is_err());
+    let err = results[0].error.as_ref().unwrap();
+    assert_eq!(err.phase(), "after_all");
+    assert!(
+        format!("{:#}", err.inner()).contains("grace period"),
+        "{err}"
+    );
+}
+
+/// Helper for `signals_are_not_swallowed_after_the_run`, run in a child process.
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn run_then_wait_for_signal() {
+    let mut tester = e2e::TestRunner::new(TestConfig::default());
+    tester.add_suite(PendingFlow::new());
+    tester.run().await.unwrap();
+    println!("run finished");
+    tokio::time::sleep(Duration::from_secs(30)).await;
+}
+
+/// Sends the signal to a child process once its run finishes, returning its exit code.
+#[cfg(unix)]
+fn signal_after_the_run(signal: &str) -> Option<i32> {
+    let mut child = common::spawn_ignored_test("run_then_wait_for_signal");
+    let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+    common::wait_for_line(&mut stdout, "run finished");
+
+    common::send_signal(child.id(), signal);
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if std::time::Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("The process ignored the signal after the run finished");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    status.code()
+}
+
+#[cfg(unix)]
+#[test]
+fn signals_are_not_swallowed_after_the_run() {
+    assert_eq!(signal_after_the_run("INT"), Some(130));
+}
+
+#[cfg(unix)]
+#[test]
+fn exit_code_matches_the_received_signal() {
+    assert_eq!(signal_after_the_run("TERM"), Some(143));
+}
@@ -0,0 +1,242 @@
+mod common;
+
+use e2e::{check, check_eq, test_suite};
+
+use self::common::{ResultsReporter, TestConfig};
+
+struct Response {
+    status: u16,
+    name: String,
+    tags: Vec<&'static str>,
+}
+
+fn response() -> Response {
+    Response {
+        status: 500,
+        name: "bob".to_string(),
+        tags: vec!["admin"],
+    }
+}
+
+#[derive(Debug)]
+struct CheckFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Check suite")]
+impl CheckFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("All checks pass")]
+    async fn all_pass(&self) -> anyhow::Result<()> {
+        let response = response();
+        check!(response.tags.contains(&"admin"));
+        check_eq!(response.name, "bob");
+        Ok(())
+    }
+
+    #[test_case("Several checks fail")]
+    async fn several_fail(&self) -> anyhow::Result<()> {
+        let response = response();
+        let ok = check_eq!(response.status, 200);
+        check_eq!(response.name, "alice", "Unexpected name");
+        check!(response.tags.is_empty());
+        self.config.log(format!("test continued, status ok: {ok}"));
+        Ok(())
+    }
+
+    #[test_case("Check fails before an error")]
+    async fn check_and_error(&self) -> anyhow::Result<()> {
+        check!(response().status == 200, "Status is {}", response().status);
+        anyhow::bail!("Request failed")
+    }
+}
+
+#[tokio::test]
+async fn failed_checks_fail_the_test_after_it_finishes() {
+    let config = TestConfig::default();
+    let (reporter, results) = ResultsReporter::new();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_reporter(Box::new(reporter));
+    tester.add_suite(CheckFlow::new());
+    tester.run().await.unwrap();
+
+    assert_eq!(config.events.get(), ["test continued, status ok: false"]);
+    let errors = common::test_errors(&results.lock().unwrap()[0]);
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0], None);
+
+    let several = errors[1].as_deref().unwrap();
+    let file = file!();
+    assert!(
+        several.starts_with("3 check(s) failed:\n")
+            && several.contains(&format!(
+                "\n  - Check `response.status == 200` failed: 500 != 200 at {file}:"
+            ))
+            && several.contains("\n  - Unexpected name: \"bob\" != \"alice\" at ")
+            && several.contains("\n  - Check `response.tags.is_empty()` failed at "),
+        "{several}"
+    );
+
+    let with_error = errors[2].as_deref().unwrap();
+    assert!(
+        with_error.starts_with("1 check(s) failed:\n  - Status is 500 at ")
+            && with_error.ends_with(": Request failed"),
+        "{with_error}"
+    );
+}
+
+#[derive(Debug)]
+struct HookCheckFlow;
+
+#[test_suite("Hook check suite")]
+impl HookCheckFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[before_all]
+    async fn before_all(&self) -> anyhow::Result<()> {
+        check!(false, "Suite is not ready");
+        Ok(())
+    }
+
+    #[test_case("Unreached")]
+    async fn unreached(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct TaskCheckFlow;
+
+#[test_suite("Task check suite")]
+impl TaskCheckFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Checks in a task")]
+    async fn checks_in_task(&self) -> anyhow::Result<()> {
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        e2e::spawn(async move {
+            check_eq!(response().status, 200, "Checked in a task");
+            let _ = done_tx.send(());
+        });
+        done_rx.await?;
+        Ok(())
+    }
+
+    #[test_case("Checks in a task after the test")]
+    async fn checks_in_task_after_test(&self) -> anyhow::Result<()> {
+        e2e::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            check!(false, "Checked after the test");
+        });
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct SuiteTaskCheckFlow;
+
+#[test_suite("Suite task check suite")]
+impl SuiteTaskCheckFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[before_all]
+    async fn before_all(&self) -> anyhow::Result<()> {
+        e2e::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            check!(false, "Checked after the suite");
+        });
+        Ok(())
+    }
+
+    #[test_case("Passing")]
+    async fn passing(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct PanickingHookFlow;
+
+#[test_suite("Panicking hook suite")]
+impl PanickingHookFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[before_each]
+    async fn before_each(&self) -> anyhow::Result<()> {
+        panic!("Hook panicked")
+    }
+
+    #[test_case("Unreached")]
+    async fn unreached(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn checks_and_panics_outside_of_test_bodies_fail_their_owner() {
+    let (reporter, results) = ResultsReporter::new();
+    let mut tester = e2e::TestRunner::new(TestConfig::default()).with_reporter(Box::new(reporter));
+    tester.add_suite(HookCheckFlow::new());
+    tester.add_suite(TaskCheckFlow::new());
+    tester.add_suite(PanickingHookFlow::new());
+    tester.add_suite(SuiteTaskCheckFlow::new());
+    tester.run().await.unwrap();
+
+    let results = results.lock().unwrap();
+    let hook = results[0].error.as_ref().unwrap();
+    let message = format!("{:#}", hook.inner());
+    assert_eq!(hook.phase(), "before_all");
+    assert!(
+        message.starts_with("1 check(s) failed:\n  - Suite is not ready at "),
+        "{message}"
+    );
+
+    let task = results[1].tests[0].error.as_ref().unwrap();
+    let message = format!("{:#}", task.inner());
+    assert_eq!(task.phase(), "test");
+    assert!(
+        message.starts_with("1 check(s) failed:\n  - Checked in a task: 500 != 200 at "),
+        "{message}"
+    );
+
+    let late = results[1].tests[1].error.as_ref().unwrap();
+    let message = format!("{:#}", late.inner());
+    assert_eq!(late.phase(), "test");
+    assert!(
+        message.starts_with("1 check(s) failed:\n  - Checked after the test at "),
+        "{message}"
+    );
+
+    let panicked = results[2].tests[0].error.as_ref().unwrap();
+    assert_eq!(panicked.phase(), "before_each");
+    assert!(
+        format!("{:#}", panicked.inner()).contains("Hook panicked"),
+        "{:#}",
+        panicked.inner()
+    );
+
+    let suite = &results[3];
+    assert!(suite.tests[0].passed());
+    let late = suite.error.as_ref().unwrap();
+    let message = format!("{:#}", late.inner());
+    assert_eq!(late.phase(), "after_all");
+    assert!(
+        message.starts_with("1 check(s) failed:\n  - Checked after the suite at "),
+        "{message}"
+    );
+}
@@ -0,0 +1,112 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate only uses a part of the helpers.
+#![allow(dead_code)]
+
+use std::{
+    io::BufRead,
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+};
+
+use e2e::{Reporter, TestSuiteResult};
+
+/// Ordered log of the events observed by suites, shared with the test that runs them.
+#[derive(Debug, Clone, Default)]
+pub struct Events(Arc<Mutex<Vec<String>>>);
+
+impl Events {
+    pub fn log(&self, event: impl Into<String>) {
+        self.0.lock().unwrap().push(event.into());
+    }
+
+    /// Returns the events logged so far.
+    pub fn get(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Configuration of the suites that only log events.
+#[derive(Debug, Clone, Default)]
+pub struct TestConfig {
+    pub events: Events,
+}
+
+impl TestConfig {
+    pub fn log(&self, event: impl Into<String>) {
+        self.events.log(event);
+    }
+}
+
+/// Reporter that stores the results of the run.
+#[derive(Debug, Default)]
+pub struct ResultsReporter {
+    results: Arc<Mutex<Vec<TestSuiteResult>>>,
+}
+
+impl ResultsReporter {
+    /// Returns the reporter, and the results that are available once the run finishes.
+    pub fn new() -> (Self, Arc<Mutex<Vec<TestSuiteResult>>>) {
+        let reporter = Self::default();
+        let results = reporter.results.clone();
+        (reporter, results)
+    }
+}
+
+impl Reporter for ResultsReporter {
+    fn name(&self) -> &'static str {
+        "ResultsReporter"
+    }
+
+    fn on_run_finished(&mut self, results: &[TestSuiteResult]) {
+        *self.results.lock().unwrap() = results.to_vec();
+    }
+}
+
+/// Returns the errors of the tests of the suite, formatted along with their causes.
+pub fn test_errors(suite: &TestSuiteResult) -> Vec<Option<String>> {
+    suite
+        .tests
+        .iter()
+        .map(|test| test.error.as_ref().map(|err| format!("{:#}", err.inner())))
+        .collect()
+}
+
+/// Runs an ignored test of the current test binary in a child process, e.g. to send it
+/// signals, with its stdout and stderr piped.
+pub fn spawn_ignored_test(name: &str) -> Child {
+    Command::new(std::env::current_exe().unwrap())
+        .args(["--ignored", "--exact", name])
+        .args(["--nocapture", "--test-threads", "1"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+/// Sends SIGINT to the process.
+#[cfg(unix)]
+pub fn interrupt(pid: u32) {
+    send_signal(pid, "INT");
+}
+
+/// Sends the signal with the given name, e.g. `TERM`, to the process.
+#[cfg(unix)]
+pub fn send_signal(pid: u32, signal: &str) {
+    let status = Command::new("kill")
+        .args([&format!("-{signal}"), &pid.to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+/// Reads the output until a line contains `marker`, returning the rest of the line.
+///
+/// Output of a child test may follow the name of the test on the same line.
+pub fn wait_for_line(output: &mut dyn BufRead, marker: &str) -> String {
+    output
+        .lines()
+        .map(Result::unwrap)
+        .find_map(|line| Some(line.split_once(marker)?.1.to_string()))
+        .unwrap_or_else(|| panic!("No line contains {marker:?}"))
+}
@@ -0,0 +1,212 @@
+#![cfg(unix)]
+
+use std::{
+    fs::File,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use e2e::{ConsoleReporter, TestRunnerConfiguration, test_suite};
+
+#[derive(Debug, Clone, Default)]
+struct TestConfig {
+    runs: Arc<AtomicUsize>,
+}
+
+#[derive(Debug, Clone)]
+struct FlakyFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Flaky suite")]
+impl FlakyFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Stable")]
+    async fn stable(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[test_case("Sometimes")]
+    async fn sometimes(&self) -> anyhow::Result<()> {
+        let run = self.config.runs.fetch_add(1, Ordering::SeqCst) + 1;
+        anyhow::ensure!(run.is_multiple_of(2), "Failed on run {run}");
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BrokenHookFlow;
+
+#[test_suite("Broken hook suite")]
+impl BrokenHookFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[before_all]
+    async fn before_all(&self) -> anyhow::Result<()> {
+        anyhow::bail!("Setup failed")
+    }
+
+    #[test_case("Unreached")]
+    async fn unreached(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Creates a console reporter writing to a file, and returns the path of the file.
+fn file_reporter(name: &str) -> (ConsoleReporter, PathBuf) {
+    let path = std::env::temp_dir().join(format!("e2e-console-{name}-{}", std::process::id()));
+    let term = console::Term::read_write_pair(
+        File::open("/dev/null").unwrap(),
+        File::create(&path).unwrap(),
+    );
+    (ConsoleReporter::new().with_term(term), path)
+}
+
+fn read_output(path: &PathBuf) -> String {
+    let output = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    output
+}
+
+#[tokio::test]
+async fn summary_reports_flaky_tests_and_rerun_commands() {
+    let (reporter, path) = file_reporter("summary");
+    let mut tester = e2e::TestRunner::new(TestConfig::default())
+        .with_runner_config(TestRunnerConfiguration::default().with_repeat(2))
+        .with_reporter(Box::new(reporter));
+    tester.add_suite(FlakyFlow::new());
+    tester.add_suite(BrokenHookFlow::new());
+    tester.run().await.unwrap();
+
+    let output = read_output(&path);
+    let summary = &output[output.find("Summary:").expect(&output)..];
+    assert!(summary.contains(", flaky: 1, "), "{summary}");
+    let rerun = &summary[summary.find("Re-run failed tests with:").expect(summary)..];
+    assert_eq!(
+        rerun.lines().skip(1).collect::<Vec<_>>(),
+        [
+            "  --test-suite-filter '^(Flaky suite)$' --test-case-filter '^(Sometimes)$'",
+            "  --test-suite-filter '^(Broken hook suite)$'",
+        ],
+        "{summary}"
+    );
+}
+
+#[derive(Debug, Clone)]
+struct FailingFlow;
+
+#[test_suite("Failing suite")]
+impl FailingFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Fails")]
+    async fn fails(&self) -> anyhow::Result<()> {
+        anyhow::bail!("Expected failure")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PassingFlow;
+
+#[test_suite("Passing suite")]
+impl PassingFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Passes")]
+    async fn passes(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn failed_suites_leave_the_live_region() {
+    let (reporter, path) = file_reporter("live-region");
+    let mut tester = e2e::TestRunner::new(TestConfig::default())
+        .with_reporter(Box::new(reporter.with_live_region(true)));
+    tester.add_suite(FailingFlow::new());
+    tester.add_suite(PassingFlow::new());
+    tester.run().await.unwrap();
+
+    let output = read_output(&path);
+    let progress = &output[..output.find("Summary:").expect(&output)];
+    let failed = progress
+        .find("❌ Test Suite: Failing suite")
+        .expect(progress);
+    assert!(
+        progress[failed..].contains("  - ❌ Fails error:"),
+        "{progress}"
+    );
+    // Once printed, the failed suite is no longer redrawn with the following suites.
+    let passing = progress.find("Test Suite: Passing suite").expect(progress);
+    assert!(!progress[passing..].contains("Failing suite"), "{progress}");
+}
+
+#[derive(Debug, Clone)]
+struct SlowFlow;
+
+#[test_suite("Slow suite")]
+impl SlowFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Slow")]
+    async fn slow(&self) -> anyhow::Result<()> {
+        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        Ok(())
+    }
+
+    #[test_case("Next")]
+    async fn next(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn cancelled_suites_and_tests_are_printed() {
+    let (reporter, path) = file_reporter("cancelled");
+    let mut tester = e2e::TestRunner::new(TestConfig::default()).with_reporter(Box::new(reporter));
+    tester.add_suite(SlowFlow::new());
+    tester.add_suite(PassingFlow::new());
+    let token = tester.cancellation_token();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        token.cancel();
+    });
+    tester.run().await.unwrap_err();
+
+    let output = read_output(&path);
+    let progress = &output[..output.find("Summary:").expect(&output)];
+    let slow = progress.find("❌ Test Suite: Slow suite").expect(progress);
+    let passing = progress
+        .find("❌ Test Suite: Passing suite")
+        .expect(progress);
+    assert!(slow < passing, "{progress}");
+    for test in ["Slow", "Next"] {
+        assert!(
+            progress[slow..passing].contains(&format!("  - ❌ {test} error:")),
+            "{progress}"
+        );
+    }
+    assert!(
+        progress[passing..].contains("  - ❌ Passes error:"),
+        "{progress}"
+    );
+}
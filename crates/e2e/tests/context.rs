@@ -0,0 +1,69 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use e2e::{RepeatMode, TestContext, TestRunnerConfiguration, test_suite};
+
+#[derive(Debug, Clone, Default)]
+struct TestConfig {
+    seen: Arc<Mutex<Vec<(String, usize, PathBuf)>>>,
+}
+
+#[derive(Debug, Clone)]
+struct TestFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Context suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("With context")]
+    async fn with_context(&self, ctx: &TestContext) -> anyhow::Result<()> {
+        anyhow::ensure!(!ctx.cancellation_token().is_cancelled());
+        let dir = ctx.temp_dir()?;
+        anyhow::ensure!(ctx.temp_dir()? == dir, "Temp dir must be created once");
+        std::fs::write(dir.join("file.txt"), "data")?;
+        self.config.seen.lock().unwrap().push((
+            ctx.id().to_string(),
+            ctx.iteration(),
+            dir.to_path_buf(),
+        ));
+        Ok(())
+    }
+
+    #[test_case("Without context")]
+    async fn without_context(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_context_is_passed_to_tests() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_runner_config(
+        TestRunnerConfiguration::default()
+            .with_repeat(2)
+            .with_repeat_mode(RepeatMode::Reuse),
+    );
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+
+    let seen = config.seen.lock().unwrap();
+    let ids: Vec<_> = seen.iter().map(|(id, it, _)| (id.as_str(), *it)).collect();
+    assert_eq!(
+        ids,
+        [
+            ("Context suite / With context", 1),
+            ("Context suite / With context", 2)
+        ]
+    );
+    assert_ne!(seen[0].2, seen[1].2, "Each test must get its own temp dir");
+    for (_, _, dir) in seen.iter() {
+        assert!(!dir.exists(), "{} must be removed", dir.display());
+    }
+}
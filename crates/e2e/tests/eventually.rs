@@ -0,0 +1,124 @@
+mod common;
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use e2e::{EventuallyError, TestRunnerConfiguration, assert_eventually, test_suite};
+
+use self::common::TestConfig;
+
+async fn drained(queue: &Mutex<Vec<u32>>) -> anyhow::Result<()> {
+    assert_eventually!(
+        Duration::from_millis(200),
+        Duration::from_millis(20),
+        queue.lock().unwrap().is_empty()
+    );
+    Ok(())
+}
+
+#[derive(Debug)]
+struct EventuallyFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Eventually suite")]
+impl EventuallyFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Condition is met")]
+    async fn met(&self) -> anyhow::Result<()> {
+        let mut attempts = 0;
+        let value = e2e::eventually(Duration::from_secs(5), Duration::from_millis(10), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                anyhow::ensure!(attempt == 3, "Attempt {attempt} is too early");
+                Ok(attempt)
+            }
+        })
+        .await?;
+        self.config.log(format!("value {value}"));
+        Ok(())
+    }
+
+    #[test_case("Condition is not met")]
+    async fn not_met(&self) -> anyhow::Result<()> {
+        let queue = Mutex::new(vec![1]);
+        let err = drained(&queue).await.unwrap_err();
+        let eventually = err.downcast_ref::<EventuallyError>().unwrap();
+        anyhow::ensure!(eventually.attempts() > 1, "{err}");
+        anyhow::ensure!(eventually.elapsed() >= Duration::from_millis(200), "{err}");
+        self.config.log(format!("{err}"));
+        Ok(())
+    }
+
+    #[test_case("Condition hangs")]
+    async fn hangs(&self) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let check = async {
+            assert_eventually!(
+                Duration::from_millis(100),
+                Duration::from_millis(10),
+                std::future::pending::<bool>().await
+            );
+            anyhow::Ok(())
+        };
+        let err = check.await.unwrap_err();
+        self.config.log(format!(
+            "stopped in time: {}, {err}",
+            started_at.elapsed() < Duration::from_millis(400)
+        ));
+        Ok(())
+    }
+
+    #[test_case("Test deadline comes first")]
+    async fn deadline(&self) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let err = e2e::eventually::<(), _, _>(
+            Duration::from_secs(30),
+            Duration::from_millis(20),
+            || async { anyhow::bail!("Never ready") },
+        )
+        .await
+        .unwrap_err();
+        self.config.log(format!(
+            "stopped early: {}, {}",
+            started_at.elapsed() < Duration::from_secs(5),
+            err.last_error()
+        ));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn eventually_retries_until_condition_is_met() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_runner_config(
+        TestRunnerConfiguration::default().with_timeout(Duration::from_millis(500)),
+    );
+    tester.add_suite(EventuallyFlow::new());
+    tester.run().await.unwrap();
+
+    let events = config.events.get();
+    assert_eq!(events.len(), 4, "{events:?}");
+    assert_eq!(events[0], "value 3");
+    assert!(
+        events[1].starts_with("Condition was not met after ")
+            && events[1]
+                .ends_with("last error: Condition `queue.lock().unwrap().is_empty()` is false"),
+        "{}",
+        events[1]
+    );
+    assert!(
+        events[2].starts_with("stopped in time: true, Condition was not met after 1 attempt(s)")
+            && events[2].ends_with("last error: Attempt did not finish before the deadline"),
+        "{}",
+        events[2]
+    );
+    assert_eq!(events[3], "stopped early: true, Never ready");
+}
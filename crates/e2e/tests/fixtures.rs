@@ -0,0 +1,180 @@
+mod common;
+
+use std::time::Duration;
+
+use e2e::{TestContext, TestRunnerConfiguration, test_suite};
+
+use self::common::{ResultsReporter, TestConfig};
+
+#[derive(Debug)]
+struct Account {
+    id: u32,
+    balance: u32,
+}
+
+struct FixtureFlow {
+    config: TestConfig,
+    next_id: u32,
+}
+
+#[test_suite("Fixture suite")]
+impl FixtureFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            config: c.clone(),
+            next_id: 0,
+        })
+    }
+
+    #[fixture(teardown = close_account)]
+    async fn account(&mut self) -> anyhow::Result<Account> {
+        self.next_id += 1;
+        self.config.log(format!("open {}", self.next_id));
+        Ok(Account {
+            id: self.next_id,
+            balance: 0,
+        })
+    }
+
+    async fn close_account(&self, account: Account) -> anyhow::Result<()> {
+        self.config
+            .log(format!("close {} with {}", account.id, account.balance));
+        Ok(())
+    }
+
+    #[fixture]
+    async fn greeting(&self) -> anyhow::Result<String> {
+        Ok("hello".to_string())
+    }
+
+    #[fixture(teardown = never_called)]
+    async fn broken(&self) -> anyhow::Result<u32> {
+        anyhow::bail!("Broken fixture")
+    }
+
+    async fn never_called(&self, _value: u32) -> anyhow::Result<()> {
+        self.config.log("never called");
+        Ok(())
+    }
+
+    #[test_case("Deposit")]
+    async fn deposit(
+        &self,
+        ctx: &TestContext,
+        account: &mut Account,
+        greeting: &String,
+    ) -> anyhow::Result<()> {
+        account.balance += 10;
+        self.config
+            .log(format!("{greeting} from {}", ctx.id().test));
+        Ok(())
+    }
+
+    #[test_case("Failing")]
+    async fn failing(&self, account: &Account) -> anyhow::Result<()> {
+        anyhow::bail!("Account {} is broken", account.id)
+    }
+
+    #[test_case("Panicking")]
+    async fn panicking(&self, _account: &Account) -> anyhow::Result<()> {
+        panic!("Expected panic")
+    }
+
+    #[test_case("Timing out")]
+    async fn timing_out(&self, _account: &Account) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+
+    #[test_case("Broken setup")]
+    async fn broken_setup(&self, _account: &Account, _broken: &u32) -> anyhow::Result<()> {
+        unreachable!("Test must not run if fixtures can't be set up")
+    }
+}
+
+#[tokio::test]
+async fn fixtures_are_torn_down_regardless_of_outcome() {
+    e2e::init();
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_runner_config(
+        TestRunnerConfiguration::default().with_timeout(Duration::from_millis(200)),
+    );
+    tester.add_suite(FixtureFlow::new());
+    tester.run().await.unwrap();
+
+    assert_eq!(
+        config.events.get(),
+        [
+            "open 1",
+            "hello from Deposit",
+            "close 1 with 10",
+            "open 2",
+            "close 2 with 0",
+            "open 3",
+            "close 3 with 0",
+            "open 4",
+            "close 4 with 0",
+            "open 5",
+            "close 5 with 0",
+        ]
+    );
+}
+
+#[derive(Debug)]
+struct SlowTeardownFlow;
+
+#[test_suite("Slow teardown suite")]
+impl SlowTeardownFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[fixture(teardown = disconnect)]
+    async fn connection(&self) -> anyhow::Result<u32> {
+        Ok(1)
+    }
+
+    async fn disconnect(&self, _connection: u32) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+
+    #[test_case("Passes")]
+    async fn passes(&self, _connection: &u32) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[test_case("Times out")]
+    async fn times_out(&self, _connection: &u32) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn fixture_teardown_is_bounded_by_its_own_timeout() {
+    let (reporter, results) = ResultsReporter::new();
+    let mut tester = e2e::TestRunner::new(TestConfig::default())
+        .with_runner_config(
+            TestRunnerConfiguration::default()
+                .with_timeout(Duration::from_millis(200))
+                .with_teardown_timeout(Duration::from_millis(100)),
+        )
+        .with_reporter(Box::new(reporter));
+    tester.add_suite(SlowTeardownFlow::new());
+    tokio::time::timeout(Duration::from_secs(5), tester.run())
+        .await
+        .expect("Teardown was not bounded")
+        .unwrap();
+
+    let errors = common::test_errors(&results.lock().unwrap()[0]);
+    assert_eq!(
+        errors,
+        [
+            Some("Fixture teardown timed out after 100ms".to_string()),
+            Some("Test timed out after 200ms".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,208 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use e2e::{HistoryReport, HistoryStore, TestRunnerConfiguration, TestStatus, test_suite};
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+struct TestConfig;
+
+#[derive(Debug, Clone)]
+struct TestFlow {
+    run: usize,
+}
+
+#[test_suite("History suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        let run = RUN_COUNTER.fetch_add(1, Ordering::SeqCst);
+        Ok(Self { run })
+    }
+
+    #[test_case("Stable")]
+    async fn stable(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Passes on even runs only.
+    #[test_case("Flaky")]
+    async fn flaky(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(self.run.is_multiple_of(2), "Odd run");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn history_detects_flaky_tests() {
+    let dir = std::env::temp_dir().join(format!("e2e-history-{}", std::process::id()));
+    for _ in 0..3 {
+        let config = TestRunnerConfiguration::default().with_history_dir(&dir);
+        let mut tester = e2e::TestRunner::new(TestConfig).with_runner_config(config);
+        tester.add_suite(TestFlow::new());
+        tester.run().await.unwrap();
+        // Ensure that run identifiers are distinct.
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+
+    let runs = HistoryStore::new(&dir).runs().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(runs.len(), 3);
+    let statuses: Vec<_> = runs
+        .iter()
+        .map(|run| run.suites[0].tests[1].status)
+        .collect();
+    assert_eq!(
+        statuses,
+        [TestStatus::Passed, TestStatus::Failed, TestStatus::Passed]
+    );
+
+    let report = HistoryReport::analyze(&runs, None).unwrap();
+    assert_eq!(report.flaky.len(), 1);
+    assert_eq!(report.flaky[0].id.test, "Flaky");
+    assert_eq!(report.flaky[0].flips, 2);
+    // Latest run has no failures.
+    assert!(report.newly_failing.is_empty());
+
+    let report = HistoryReport::analyze(&runs[..2], None).unwrap();
+    assert_eq!(
+        report.baseline_run.as_deref(),
+        Some(runs[0].run_id.as_str())
+    );
+    assert_eq!(report.newly_failing.len(), 1);
+}
+
+static STABLE_RUNS: AtomicUsize = AtomicUsize::new(0);
+static BROKEN_RUNS: AtomicUsize = AtomicUsize::new(0);
+static OTHER_SUITE_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+struct RerunFlow;
+
+#[test_suite("Rerun suite")]
+impl RerunFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Stable")]
+    async fn stable(&self) -> anyhow::Result<()> {
+        STABLE_RUNS.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    #[test_case("Broken")]
+    async fn broken(&self) -> anyhow::Result<()> {
+        BROKEN_RUNS.fetch_add(1, Ordering::SeqCst);
+        anyhow::bail!("Always fails")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct OtherFlow;
+
+#[test_suite("Other suite")]
+impl OtherFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        OTHER_SUITE_RUNS.fetch_add(1, Ordering::SeqCst);
+        Ok(Self)
+    }
+
+    #[test_case("Passing")]
+    async fn passing(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn rerun_failed_runs_only_failed_tests() {
+    let dir = std::env::temp_dir().join(format!("e2e-rerun-{}", std::process::id()));
+    for rerun_failed in [false, true] {
+        let config = TestRunnerConfiguration::default()
+            .with_history_dir(&dir)
+            .with_rerun_failed(rerun_failed);
+        let mut tester = e2e::TestRunner::new(TestConfig).with_runner_config(config);
+        tester.add_suite(RerunFlow::new());
+        tester.add_suite(OtherFlow::new());
+        tester.run().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    let last_run = HistoryStore::new(&dir).last_run().unwrap().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(STABLE_RUNS.load(Ordering::SeqCst), 1);
+    assert_eq!(BROKEN_RUNS.load(Ordering::SeqCst), 2);
+    assert_eq!(OTHER_SUITE_RUNS.load(Ordering::SeqCst), 1);
+    assert_eq!(last_run.suites.len(), 1);
+    let statuses: Vec<_> = last_run.suites[0]
+        .tests
+        .iter()
+        .map(|test| test.status)
+        .collect();
+    assert_eq!(statuses, [TestStatus::Ignored, TestStatus::Failed]);
+}
+
+static REPEATED_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+struct RepeatedFlow;
+
+#[test_suite("Repeated history suite")]
+impl RepeatedFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Stable")]
+    async fn stable(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Fails on the second run only.
+    #[test_case("Flaky")]
+    async fn flaky(&self) -> anyhow::Result<()> {
+        let run = REPEATED_RUNS.fetch_add(1, Ordering::SeqCst) + 1;
+        anyhow::ensure!(run != 2, "Second run");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn repeated_tests_are_recorded_once() {
+    let dir = std::env::temp_dir().join(format!("e2e-history-repeat-{}", std::process::id()));
+    let config = TestRunnerConfiguration::default()
+        .with_history_dir(&dir)
+        .with_repeat(3);
+    let mut tester = e2e::TestRunner::new(TestConfig).with_runner_config(config);
+    tester.add_suite(RepeatedFlow::new());
+    tester.run().await.unwrap();
+
+    let runs = HistoryStore::new(&dir).runs().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].suites.len(), 1);
+    let suite = &runs[0].suites[0];
+    assert!(!suite.passed);
+    let records: Vec<_> = suite
+        .tests
+        .iter()
+        .map(|test| {
+            (
+                test.id.test.as_str(),
+                test.status,
+                test.attempts,
+                test.iteration,
+            )
+        })
+        .collect();
+    assert_eq!(
+        records,
+        [
+            ("Stable", TestStatus::Passed, 3, 1),
+            ("Flaky", TestStatus::Failed, 3, 2),
+        ]
+    );
+}
@@ -0,0 +1,66 @@
+use e2e::{TestMetadata, TestRunnerConfiguration, test_suite};
+
+#[derive(Debug, Clone)]
+struct TestConfig;
+
+#[derive(Debug, Clone)]
+struct TestFlow;
+
+#[test_suite("Listed suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        panic!("Listing must not create suites");
+    }
+
+    #[constructor("variant")]
+    async fn variant(_c: &TestConfig) -> anyhow::Result<Self> {
+        panic!("Listing must not create suites");
+    }
+
+    #[test_case("Smoke", tags = ["smoke", "fast"])]
+    async fn smoke(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[test_case("Slow", ignore)]
+    async fn slow(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn list_does_not_create_suites() {
+    let mut tester = e2e::TestRunner::new(TestConfig);
+    tester.add_suite(TestFlow::new());
+    tester.add_suite(TestFlow::variant());
+
+    let suites = tester.list().unwrap();
+    let names: Vec<_> = suites.iter().map(|suite| suite.name.as_str()).collect();
+    assert_eq!(names, ["Listed suite", "Listed suite (variant)"]);
+    assert_eq!(
+        suites[1].tests,
+        [
+            TestMetadata::new("Smoke").with_tags(vec!["smoke".to_string(), "fast".to_string()]),
+            TestMetadata::new("Slow").with_ignore(true),
+        ]
+    );
+}
+
+#[test]
+fn list_respects_filters() {
+    let config = TestRunnerConfiguration::default()
+        .with_test_suite_filter(regex::Regex::new("variant").unwrap())
+        .with_test_case_filter(regex::Regex::new("^Slow$").unwrap());
+    let mut tester = e2e::TestRunner::new(TestConfig).with_runner_config(config);
+    tester.add_suite(TestFlow::new());
+    tester.add_suite(TestFlow::variant());
+
+    let suites = tester.list().unwrap();
+    assert_eq!(suites.len(), 1);
+    assert_eq!(suites[0].id.constructor_variant.as_deref(), Some("variant"));
+    assert_eq!(
+        suites[0].tests,
+        [TestMetadata::new("Slow").with_ignore(true)]
+    );
+}
@@ -0,0 +1,63 @@
+use std::sync::{Arc, Mutex};
+
+use e2e::test_suite;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+#[derive(Debug, Clone, Default)]
+struct TestConfig {
+    received: Arc<Mutex<Vec<Option<u32>>>>,
+}
+
+/// Suite holding handles that cannot be cloned.
+struct ProducerFlow {
+    config: TestConfig,
+    producer: JoinHandle<()>,
+    receiver: mpsc::Receiver<u32>,
+}
+
+#[test_suite("Non-Clone suite")]
+impl ProducerFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        let (sender, receiver) = mpsc::channel(1);
+        let producer = tokio::spawn(async move {
+            for value in 1.. {
+                if sender.send(value).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            config: c.clone(),
+            producer,
+            receiver,
+        })
+    }
+
+    #[after_all]
+    async fn after_all(&self) -> anyhow::Result<()> {
+        self.producer.abort();
+        Ok(())
+    }
+
+    #[test_case("Receive")]
+    async fn receive(&mut self) -> anyhow::Result<()> {
+        let value = self.receiver.recv().await;
+        self.config.received.lock().unwrap().push(value);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn suites_do_not_have_to_be_clone() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_runner_config(
+        e2e::TestRunnerConfiguration::default()
+            .with_repeat(2)
+            .with_repeat_mode(e2e::RepeatMode::Reuse),
+    );
+    tester.add_suite(ProducerFlow::new());
+    tester.run().await.unwrap();
+
+    assert_eq!(*config.received.lock().unwrap(), [Some(1), Some(2)]);
+}
@@ -0,0 +1,285 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{
+    io::BufReader,
+    process::Command,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use e2e::{
+    TestRunnerConfiguration,
+    process::{Readiness, Service},
+    test_suite,
+};
+
+use self::common::Events;
+
+#[derive(Debug, Clone, Default)]
+struct TestConfig {
+    events: Events,
+    pids: Arc<Mutex<Vec<u32>>>,
+}
+
+fn shell(script: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(script);
+    command
+}
+
+/// Whether the process exists and is not a zombie, which killed processes can be when they
+/// are orphaned.
+fn is_alive(pid: u32) -> bool {
+    let output = Command::new("ps")
+        .args(["-o", "stat=", "-p", &pid.to_string()])
+        .output()
+        .unwrap();
+    output.status.success()
+        && !String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .starts_with('Z')
+}
+
+#[derive(Debug)]
+struct ServiceFlow {
+    config: TestConfig,
+    server: Option<Service>,
+}
+
+#[test_suite("Service suite")]
+impl ServiceFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            config: c.clone(),
+            server: None,
+        })
+    }
+
+    #[before_all]
+    async fn before_all(&mut self) -> anyhow::Result<()> {
+        let server = Service::builder(
+            "server",
+            shell("echo starting >&2; sleep 0.1; echo ready; exec sleep 30"),
+        )
+        .with_readiness(Readiness::log_line("^ready$"))
+        .start()
+        .await?;
+        self.config.pids.lock().unwrap().extend(server.pid());
+        self.server = Some(server);
+        Ok(())
+    }
+
+    #[test_case("Suite service is running")]
+    async fn suite_service(&self) -> anyhow::Result<()> {
+        let server = self.server.as_ref().unwrap();
+        anyhow::ensure!(server.exit_status().is_none(), "Server must be running");
+        self.config.events.log(server.output());
+        Ok(())
+    }
+
+    #[test_case("Test service is stopped")]
+    async fn test_service(&self) -> anyhow::Result<()> {
+        let mut service = Service::builder("worker", shell("exec sleep 30"))
+            .start()
+            .await?;
+        self.config.pids.lock().unwrap().extend(service.pid());
+        let status = service.stop().await?;
+        self.config
+            .events
+            .log(format!("worker stopped: {}", status.success()));
+        Ok(())
+    }
+
+    #[test_case("Service exits before becoming ready")]
+    async fn exits_early(&self) -> anyhow::Result<()> {
+        let err = Service::builder("broken", shell("echo boom; exit 3"))
+            .with_readiness(Readiness::log_line("ready"))
+            .with_poll_interval(Duration::from_millis(10))
+            .start()
+            .await
+            .unwrap_err();
+        self.config.events.log(format!("{err:#}"));
+        Ok(())
+    }
+
+    #[test_case("Health check never succeeds")]
+    async fn never_ready(&self) -> anyhow::Result<()> {
+        let err = Service::builder("stuck", shell("exec sleep 30"))
+            .with_readiness(Readiness::check(|| async { anyhow::bail!("Not yet") }))
+            .with_startup_timeout(Duration::from_millis(200))
+            .with_poll_interval(Duration::from_millis(10))
+            .start()
+            .await
+            .unwrap_err();
+        self.config.events.log(format!("{err:#}"));
+        Ok(())
+    }
+
+    #[after_all]
+    async fn after_all(&self) -> anyhow::Result<()> {
+        let server = self.server.as_ref().unwrap();
+        self.config.events.log(format!(
+            "server running in after_all: {}",
+            server.exit_status().is_none()
+        ));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn services_are_killed_with_their_owner() {
+    let dir = std::env::temp_dir().join(format!("e2e-process-test-{}", std::process::id()));
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone())
+        .with_runner_config(TestRunnerConfiguration::default().with_artifacts_dir(&dir));
+    tester.add_suite(ServiceFlow::new());
+    tester.run().await.unwrap();
+
+    let events = config.events.get();
+    assert_eq!(events.len(), 5, "{events:?}");
+    assert_eq!(events[0], "starting\nready\n");
+    assert_eq!(events[1], "worker stopped: false");
+    assert!(
+        events[2].contains("Service broken exited with exit status: 3 before becoming ready")
+            && events[2].contains("boom"),
+        "{}",
+        events[2]
+    );
+    assert!(
+        events[3].contains("Service stuck is not ready after 200ms: Not yet"),
+        "{}",
+        events[3]
+    );
+    assert_eq!(events[4], "server running in after_all: true");
+
+    let pids = config.pids.lock().unwrap().clone();
+    assert_eq!(pids.len(), 2);
+    assert!(pids.iter().all(|pid| !is_alive(*pid)), "{pids:?}");
+
+    let log = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|run| {
+            run.unwrap()
+                .path()
+                .join("Service_suite/@artifacts/server.log")
+        })
+        .next()
+        .unwrap();
+    let log = std::fs::read_to_string(log).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(log, "starting\nready\n");
+}
+
+#[derive(Debug)]
+struct OutputFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Output suite")]
+impl OutputFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Invalid UTF-8")]
+    async fn invalid_utf8(&self) -> anyhow::Result<()> {
+        let service =
+            Service::builder("latin1", shell(r"printf 'caf\351\nready\n'; exec sleep 30"))
+                .with_readiness(Readiness::log_line("^ready$"))
+                .start()
+                .await?;
+        self.config.events.log(service.output());
+        Ok(())
+    }
+
+    #[test_case("Long output")]
+    async fn long_output(&self) -> anyhow::Result<()> {
+        let service = Service::builder("verbose", shell("seq 1 20000; echo done; exec sleep 30"))
+            .with_readiness(Readiness::log_line("^done$"))
+            .start()
+            .await?;
+        self.config.events.log(service.output());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn output_is_decoded_lossily_and_bounded() {
+    let dir = std::env::temp_dir().join(format!("e2e-output-test-{}", std::process::id()));
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone())
+        .with_runner_config(TestRunnerConfiguration::default().with_artifacts_dir(&dir));
+    tester.add_suite(OutputFlow::new());
+    tester.run().await.unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let events = config.events.get();
+    assert_eq!(events.len(), 2, "{events:?}");
+    assert_eq!(events[0], "caf\u{FFFD}\nready\n");
+    let tail = &events[1];
+    assert!(tail.len() <= 64 * 1024, "{}", tail.len());
+    assert!(tail.ends_with("19999\n20000\ndone\n"));
+    // Only whole lines are kept.
+    let first: usize = tail.lines().next().unwrap().parse().unwrap();
+    assert!(tail.starts_with(&format!("{first}\n{}\n", first + 1)));
+}
+
+#[derive(Debug)]
+struct StuckFlow {
+    server: Option<Service>,
+}
+
+#[test_suite("Stuck suite")]
+impl StuckFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { server: None })
+    }
+
+    #[before_all]
+    async fn before_all(&mut self) -> anyhow::Result<()> {
+        let server = Service::builder("server", shell("exec sleep 30"))
+            .start()
+            .await?;
+        println!("pid {}", server.pid().unwrap());
+        self.server = Some(server);
+        Ok(())
+    }
+
+    #[test_case("Blocks the runtime")]
+    async fn blocks(&self) -> anyhow::Result<()> {
+        std::thread::sleep(Duration::from_secs(30));
+        Ok(())
+    }
+}
+
+/// Helper for `services_are_killed_on_the_second_interrupt`, run in a child process.
+#[tokio::test]
+#[ignore]
+async fn run_stuck_suite() {
+    let mut tester = e2e::TestRunner::new(TestConfig::default());
+    tester.add_suite(StuckFlow::new());
+    tester.run().await.unwrap_err();
+}
+
+#[test]
+fn services_are_killed_on_the_second_interrupt() {
+    let mut child = common::spawn_ignored_test("run_stuck_suite");
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut stderr = BufReader::new(child.stderr.take().unwrap());
+
+    let pid: u32 = common::wait_for_line(&mut stdout, "pid ").parse().unwrap();
+    common::interrupt(child.id());
+    common::wait_for_line(&mut stderr, "Interrupted:");
+    // The runtime is blocked by the test, so the service can't be stopped by the runner.
+    assert!(is_alive(pid));
+    common::interrupt(child.id());
+
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(130));
+    assert!(!is_alive(pid), "Service {pid} outlived the process");
+}
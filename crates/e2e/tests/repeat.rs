@@ -0,0 +1,142 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use e2e::{
+    AsyncReporter, RepeatMode, RepeatReport, ReporterEvent, TestRunnerConfiguration,
+    TestSuiteResult, test_suite,
+};
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+struct TestConfig {
+    /// Run on which the test starts failing.
+    fail_on: usize,
+}
+
+#[derive(Debug, Clone)]
+struct TestFlow {
+    fail_on: usize,
+}
+
+#[test_suite("Repeated suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        CREATED.fetch_add(1, Ordering::SeqCst);
+        Ok(Self { fail_on: c.fail_on })
+    }
+
+    #[test_case("Racy")]
+    async fn racy(&self) -> anyhow::Result<()> {
+        let run = RUNS.fetch_add(1, Ordering::SeqCst) + 1;
+        anyhow::ensure!(run < self.fail_on, "Race reproduced on run {run}");
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct ResultsCollector(Arc<Mutex<Vec<TestSuiteResult>>>);
+
+#[async_trait::async_trait]
+impl AsyncReporter for ResultsCollector {
+    fn name(&self) -> &'static str {
+        "ResultsCollector"
+    }
+
+    async fn on_event(&mut self, event: ReporterEvent) {
+        if let ReporterEvent::RunFinished(results) = event {
+            *self.0.lock().unwrap() = results;
+        }
+    }
+}
+
+/// Runs the suite, returning the number of created suites, test runs and the results.
+async fn run(
+    fail_on: usize,
+    config: TestRunnerConfiguration,
+) -> (usize, usize, Vec<TestSuiteResult>) {
+    CREATED.store(0, Ordering::SeqCst);
+    RUNS.store(0, Ordering::SeqCst);
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let mut tester = e2e::TestRunner::new(TestConfig { fail_on }).with_runner_config(config);
+    tester.add_async_reporter(Box::new(ResultsCollector(results.clone())));
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+    let results = std::mem::take(&mut *results.lock().unwrap());
+    (
+        CREATED.load(Ordering::SeqCst),
+        RUNS.load(Ordering::SeqCst),
+        results,
+    )
+}
+
+#[tokio::test]
+async fn repeat_modes() {
+    let config = TestRunnerConfiguration::default().with_repeat(3);
+    let (created, runs, results) = run(usize::MAX, config).await;
+    assert_eq!((created, runs, results.len()), (3, 3, 3));
+
+    let config = TestRunnerConfiguration::default()
+        .with_repeat(3)
+        .with_repeat_mode(RepeatMode::Reuse);
+    let (created, runs, results) = run(usize::MAX, config).await;
+    assert_eq!((created, runs, results.len()), (1, 3, 1));
+    let iterations: Vec<_> = results[0].tests.iter().map(|t| t.iteration).collect();
+    assert_eq!(iterations, [1, 2, 3]);
+
+    // Repeat up to 10 times, but stop on the first failure.
+    let config = TestRunnerConfiguration::default()
+        .with_repeat(10)
+        .with_until_failure(true);
+    let (created, runs, results) = run(4, config).await;
+    assert_eq!((created, runs), (4, 4));
+    let report = RepeatReport::new(&results);
+    let stats = report.tests.values().next().unwrap();
+    assert_eq!((stats.passed, stats.failed), (3, 1));
+}
+
+#[derive(Debug, Clone)]
+struct OtherFlow;
+
+#[test_suite("Other repeated suite")]
+impl OtherFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Stable")]
+    async fn stable(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+struct Args {
+    #[clap(flatten)]
+    runner: TestRunnerConfiguration,
+}
+
+#[tokio::test]
+async fn invalid_repeat_options_are_rejected() {
+    use clap::Parser as _;
+
+    assert!(Args::try_parse_from(["e2e", "--repeat", "0"]).is_err());
+    assert!(Args::try_parse_from(["e2e", "--repeat", "1"]).is_ok());
+
+    let config = TestRunnerConfiguration::default().with_until_failure(true);
+    let mut tester = e2e::TestRunner::new(TestConfig { fail_on: 1 }).with_runner_config(config);
+    tester.add_suite(TestFlow::new());
+    tester.add_suite(OtherFlow::new());
+    let err = tester.run().await.unwrap_err();
+    assert!(
+        err.to_string().contains(
+            "`--until-failure` without `--repeat` requires selecting a single test suite"
+        ),
+        "{err}"
+    );
+}
@@ -0,0 +1,255 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use e2e::{
+    AsyncReporter, HtmlReporter, LogCaptureLayer, Reporter, ReporterEvent, TapReporter, TestError,
+    TestId, TestRunnerConfiguration, test_suite,
+};
+use tracing_subscriber::layer::SubscriberExt as _;
+
+#[derive(Debug, Clone)]
+struct TestConfig;
+
+#[derive(Debug, Clone)]
+struct TestFlow;
+
+#[test_suite("Reporter suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Passing")]
+    async fn passing(&self) -> anyhow::Result<()> {
+        tracing::info!(answer = 42, "Hello from the test");
+        Ok(())
+    }
+
+    #[test_case("Failing")]
+    async fn failing(&self) -> anyhow::Result<()> {
+        anyhow::bail!("Expected failure")
+    }
+
+    #[test_case("Ignored", ignore)]
+    async fn ignored(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SkippedFlow;
+
+#[test_suite("Skipped suite")]
+impl SkippedFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Never run")]
+    async fn never_run(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DuplicateFlow;
+
+#[test_suite("Duplicate suite")]
+impl DuplicateFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[constructor("variant")]
+    async fn variant(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Login")]
+    async fn login_as_admin(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[test_case("Login")]
+    async fn login_as_guest(&self) -> anyhow::Result<()> {
+        anyhow::bail!("Guests can't log in")
+    }
+
+    #[test_case("Logout")]
+    async fn logout(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reporter that records the tests it's notified about.
+#[derive(Debug, Default)]
+struct IdsReporter {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Reporter for IdsReporter {
+    fn name(&self) -> &'static str {
+        "IdsReporter"
+    }
+
+    fn on_test_start(&mut self, id: &TestId) {
+        self.events.lock().unwrap().push(format!("start {id}"));
+    }
+
+    fn on_test_end(&mut self, id: &TestId, error: Option<&TestError>) {
+        let outcome = if error.is_some() { "failed" } else { "passed" };
+        self.events.lock().unwrap().push(format!("{outcome} {id}"));
+    }
+}
+
+/// Reporter that emulates a slow sink.
+#[derive(Debug)]
+struct SlowReporter {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl AsyncReporter for SlowReporter {
+    fn name(&self) -> &'static str {
+        "SlowReporter"
+    }
+
+    async fn on_event(&mut self, event: ReporterEvent) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let event = match event {
+            ReporterEvent::TestStarted(id) => format!("start {id}"),
+            ReporterEvent::TestFinished(id, error) => format!("end {id} {}", error.is_none()),
+            ReporterEvent::RunFinished(results) => format!("finished {}", results.len()),
+            _ => return,
+        };
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[tokio::test]
+async fn async_reporter_receives_all_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut tester = e2e::TestRunner::new(TestConfig);
+    tester.add_async_reporter(Box::new(SlowReporter {
+        events: events.clone(),
+    }));
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "start Reporter suite / Passing",
+            "end Reporter suite / Passing true",
+            "start Reporter suite / Failing",
+            "end Reporter suite / Failing false",
+            "finished 1",
+        ]
+    );
+}
+
+#[derive(Debug, Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn tap_reporter_output() {
+    let buffer = SharedBuffer::default();
+    let mut tester =
+        e2e::TestRunner::new(TestConfig).with_reporter(Box::new(TapReporter::new(buffer.clone())));
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let expected = "\
+TAP version 14
+# Subtest: Reporter suite
+    ok 1 - Passing
+    not ok 2 - Failing
+      ---
+      phase: test
+      message: |-
+        Expected failure
+      ...
+    ok 3 - Ignored # SKIP
+    1..3
+not ok 1 - Reporter suite
+1..1
+";
+    assert_eq!(output, expected);
+}
+
+#[tokio::test]
+async fn html_report_contains_results_and_logs() {
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(LogCaptureLayer));
+
+    let path = std::env::temp_dir().join(format!("e2e-report-{}.html", std::process::id()));
+    let mut tester = e2e::TestRunner::new(TestConfig)
+        .with_runner_config(
+            TestRunnerConfiguration::default()
+                .with_test_suite_filter(regex::Regex::new("^Reporter suite$").unwrap()),
+        )
+        .with_reporter(Box::new(HtmlReporter::new(&path)));
+    tester.add_suite(TestFlow::new());
+    tester.add_suite(SkippedFlow::new());
+    tester.run().await.unwrap();
+
+    let html = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(html.contains("Passed: 1, failed: 1, ignored: 1"));
+    assert!(html.contains("data-name=\"Reporter suite\" data-status=\"failed\""));
+    assert!(html.contains("<li><pre>Expected failure</pre></li>"));
+    assert!(html.contains("data-name=\"Skipped suite\" data-status=\"ignored\""));
+    assert!(html.contains("Hello from the test answer=42"));
+}
+
+#[tokio::test]
+async fn reporters_tell_apart_duplicate_tests_and_suite_variants() {
+    let reporter = IdsReporter::default();
+    let events = reporter.events.clone();
+    let mut tester = e2e::TestRunner::new(TestConfig).with_reporter(Box::new(reporter));
+    tester.add_suite(DuplicateFlow::new());
+    tester.add_suite(DuplicateFlow::variant());
+    tester.run().await.unwrap();
+
+    let expected: Vec<_> = ["Duplicate suite", "Duplicate suite (variant)"]
+        .into_iter()
+        .flat_map(|suite| {
+            [
+                format!("start {suite} / Login #0"),
+                format!("passed {suite} / Login #0"),
+                format!("start {suite} / Login #1"),
+                format!("failed {suite} / Login #1"),
+                format!("start {suite} / Logout"),
+                format!("passed {suite} / Logout"),
+            ]
+        })
+        .collect();
+    assert_eq!(*events.lock().unwrap(), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn runner_with_reporters_can_be_spawned() {
+    let mut tester = e2e::TestRunner::new(TestConfig)
+        .with_reporter(Box::new(TapReporter::new(std::io::sink())))
+        .with_reporter(Box::new(e2e::ConsoleReporter::new()));
+    tester.add_suite(TestFlow::new());
+    // Fails to compile if the future returned by `run` is not `Send`.
+    tokio::spawn(tester.run()).await.unwrap().unwrap();
+}
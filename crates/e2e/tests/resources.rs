@@ -0,0 +1,270 @@
+mod common;
+
+use std::sync::Arc;
+
+use e2e::{Resource, test_suite};
+
+use self::common::{Events, ResultsReporter};
+
+#[derive(Debug, Clone, Default)]
+struct TestConfig {
+    events: Events,
+    fail_cache: bool,
+}
+
+struct Database {
+    config: TestConfig,
+    url: String,
+}
+
+#[async_trait::async_trait]
+impl Resource<TestConfig> for Database {
+    async fn create(config: &TestConfig) -> anyhow::Result<Self> {
+        config.events.log("create database");
+        Ok(Self {
+            config: config.clone(),
+            url: "postgres://localhost/test".to_string(),
+        })
+    }
+
+    async fn teardown(&self) -> anyhow::Result<()> {
+        self.config.events.log("teardown database");
+        Ok(())
+    }
+}
+
+struct Cache {
+    config: TestConfig,
+}
+
+#[async_trait::async_trait]
+impl Resource<TestConfig> for Cache {
+    async fn create(config: &TestConfig) -> anyhow::Result<Self> {
+        anyhow::ensure!(!config.fail_cache, "Cache is unavailable");
+        config.events.log("create cache");
+        Ok(Self {
+            config: config.clone(),
+        })
+    }
+
+    async fn teardown(&self) -> anyhow::Result<()> {
+        self.config.events.log("teardown cache");
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct UsersFlow {
+    config: TestConfig,
+    db_url: String,
+}
+
+#[test_suite("Users")]
+impl UsersFlow {
+    #[constructor]
+    async fn new(c: &TestConfig, db: Arc<Database>, _cache: Arc<Cache>) -> anyhow::Result<Self> {
+        Ok(Self {
+            config: c.clone(),
+            db_url: db.url.clone(),
+        })
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        self.config
+            .events
+            .log(format!("users test {}", self.db_url));
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct OrdersFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Orders")]
+impl OrdersFlow {
+    #[constructor]
+    async fn new(c: &TestConfig, _db: Arc<Database>) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        self.config.events.log("orders test");
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct PlainFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Plain")]
+impl PlainFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        self.config.events.log("plain test");
+        Ok(())
+    }
+}
+
+/// Resource whose creation panics.
+struct Flaky;
+
+#[async_trait::async_trait]
+impl Resource<TestConfig> for Flaky {
+    async fn create(config: &TestConfig) -> anyhow::Result<Self> {
+        config.events.log("create flaky");
+        panic!("Flaky resource panicked")
+    }
+}
+
+/// Resource whose teardown panics.
+struct Leaky;
+
+#[async_trait::async_trait]
+impl Resource<TestConfig> for Leaky {
+    async fn create(config: &TestConfig) -> anyhow::Result<Self> {
+        config.events.log("create leaky");
+        Ok(Self)
+    }
+
+    async fn teardown(&self) -> anyhow::Result<()> {
+        panic!("Leaky resource panicked")
+    }
+}
+
+#[derive(Debug)]
+struct FirstFlakyFlow;
+
+#[test_suite("First flaky")]
+impl FirstFlakyFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig, _flaky: Arc<Flaky>) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct SecondFlakyFlow;
+
+#[test_suite("Second flaky")]
+impl SecondFlakyFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig, _flaky: Arc<Flaky>) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct LeakyFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Leaky")]
+impl LeakyFlow {
+    #[constructor]
+    async fn new(c: &TestConfig, _leaky: Arc<Leaky>) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        self.config.events.log("leaky test");
+        Ok(())
+    }
+}
+
+async fn run(config: TestConfig) -> Vec<String> {
+    let mut tester = e2e::TestRunner::new(config.clone());
+    tester.add_suite(UsersFlow::new());
+    tester.add_suite(OrdersFlow::new());
+    tester.add_suite(PlainFlow::new());
+    tester.run().await.unwrap();
+    config.events.get()
+}
+
+#[tokio::test]
+async fn resources_are_shared_and_torn_down_after_last_user() {
+    let events = run(TestConfig::default()).await;
+    assert_eq!(
+        events,
+        [
+            "create database",
+            "create cache",
+            "users test postgres://localhost/test",
+            "teardown cache",
+            "orders test",
+            "teardown database",
+            "plain test",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn resource_creation_failure_fails_suite_creation() {
+    let events = run(TestConfig {
+        fail_cache: true,
+        ..TestConfig::default()
+    })
+    .await;
+    // The database is created before the cache fails, and is still shared with `Orders`.
+    assert_eq!(
+        events,
+        [
+            "create database",
+            "orders test",
+            "teardown database",
+            "plain test",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn resource_panics_fail_their_users_and_creation_is_not_retried() {
+    let config = TestConfig::default();
+    let (reporter, results) = ResultsReporter::new();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_reporter(Box::new(reporter));
+    tester.add_suite(FirstFlakyFlow::new());
+    tester.add_suite(SecondFlakyFlow::new());
+    tester.add_suite(LeakyFlow::new());
+    let err = tester.run().await.unwrap_err();
+
+    assert!(
+        format!("{err:#}").contains("Leaky resource panicked"),
+        "{err:#}"
+    );
+    assert_eq!(
+        config.events.get(),
+        ["create flaky", "create leaky", "leaky test"]
+    );
+    let results = results.lock().unwrap();
+    for suite in &results[..2] {
+        let err = suite.error.as_ref().unwrap();
+        assert_eq!(err.phase(), "create_suite");
+        assert!(
+            format!("{:#}", err.inner()).contains("Flaky resource panicked"),
+            "{:#}",
+            err.inner()
+        );
+    }
+    assert!(results[2].passed);
+}
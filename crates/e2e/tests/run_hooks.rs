@@ -0,0 +1,98 @@
+mod common;
+
+use e2e::{RunHooks, test_suite};
+
+use self::common::TestConfig;
+
+#[derive(Debug, Clone)]
+struct TestFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Suite with run hooks")]
+impl TestFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        self.config.log("test");
+        anyhow::bail!("Failing tests must not prevent teardown")
+    }
+}
+
+struct Hooks {
+    name: &'static str,
+    fail_setup: bool,
+}
+
+#[async_trait::async_trait]
+impl RunHooks<TestConfig> for Hooks {
+    async fn before_all(&self, config: &TestConfig) -> anyhow::Result<()> {
+        config.log(format!("{} before_all", self.name));
+        anyhow::ensure!(!self.fail_setup, "Setup failed");
+        Ok(())
+    }
+
+    async fn after_all(&self, config: &TestConfig) -> anyhow::Result<()> {
+        config.log(format!("{} after_all", self.name));
+        Ok(())
+    }
+}
+
+async fn run(fail_setup: bool) -> (Vec<String>, anyhow::Result<()>) {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone());
+    tester.add_run_hooks(Box::new(Hooks {
+        name: "first",
+        fail_setup: false,
+    }));
+    tester.add_run_hooks(Box::new(Hooks {
+        name: "second",
+        fail_setup,
+    }));
+    tester.add_run_hooks(Box::new(Hooks {
+        name: "third",
+        fail_setup: false,
+    }));
+    tester.add_suite(TestFlow::new());
+    let result = tester.run().await;
+    let events = config.events.get();
+    (events, result)
+}
+
+#[tokio::test]
+async fn run_hooks_wrap_all_suites() {
+    let (events, result) = run(false).await;
+    result.unwrap();
+    assert_eq!(
+        events,
+        [
+            "first before_all",
+            "second before_all",
+            "third before_all",
+            "test",
+            "third after_all",
+            "second after_all",
+            "first after_all",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn run_hooks_teardown_after_failed_setup() {
+    let (events, result) = run(true).await;
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("'before_all'"), "{err}");
+    assert_eq!(
+        events,
+        [
+            "first before_all",
+            "second before_all",
+            "second after_all",
+            "first after_all",
+        ]
+    );
+}
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use e2e::{Shard, TestRunnerConfiguration, test_suite};
+
+#[derive(Debug, Clone)]
+struct TestConfig;
+
+#[derive(Debug, Clone)]
+struct TestFlow;
+
+#[test_suite("Sharded suite")]
+impl TestFlow {
+    #[constructor("a")]
+    async fn a(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[constructor("b")]
+    async fn b(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[constructor("c")]
+    async fn c(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[constructor("d")]
+    async fn d(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[constructor("e")]
+    async fn e(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Test")]
+    async fn test(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+fn listed_suites(config: TestRunnerConfiguration) -> Vec<String> {
+    let mut tester = e2e::TestRunner::new(TestConfig).with_runner_config(config);
+    tester.add_suite(TestFlow::a());
+    tester.add_suite(TestFlow::b());
+    tester.add_suite(TestFlow::c());
+    tester.add_suite(TestFlow::d());
+    tester.add_suite(TestFlow::e());
+    tester
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|suite| suite.name)
+        .collect()
+}
+
+fn assert_partition(shards: &[Vec<String>]) {
+    let mut seen = HashSet::new();
+    for suite in shards.iter().flatten() {
+        assert!(seen.insert(suite.clone()), "{suite} is in several shards");
+    }
+    assert_eq!(seen.len(), 5);
+}
+
+#[test]
+fn shards_partition_suites() {
+    let shards: Vec<_> = (1..=3)
+        .map(|index| {
+            let config =
+                TestRunnerConfiguration::default().with_shard(Shard::new(index, 3).unwrap());
+            listed_suites(config)
+        })
+        .collect();
+    assert_partition(&shards);
+
+    // Assignment is deterministic.
+    let config = TestRunnerConfiguration::default().with_shard("2/3".parse().unwrap());
+    assert_eq!(listed_suites(config), shards[1]);
+}
+
+#[test]
+fn shards_are_balanced_by_durations() {
+    let suite = |variant: &str, duration_ms: u64| {
+        serde_json::json!({
+            "id": { "suite": "Sharded suite", "constructor_variant": variant },
+            "passed": true,
+            "error_phase": null,
+            "duration_ms": duration_ms,
+            "tests": [],
+        })
+    };
+    let run = serde_json::json!({
+        "run_id": "run-1",
+        "started_at_ms": 1,
+        "suites": [suite("a", 100), suite("b", 60), suite("c", 30), suite("d", 30)],
+    });
+    let path = std::env::temp_dir().join(format!("e2e-shard-{}.json", std::process::id()));
+    std::fs::write(&path, run.to_string()).unwrap();
+
+    let shards: Vec<_> = (1..=2)
+        .map(|index| {
+            let config = TestRunnerConfiguration::default()
+                .with_shard(Shard::new(index, 2).unwrap())
+                .with_shard_durations(&path);
+            listed_suites(config)
+        })
+        .collect();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_partition(&shards);
+    // Unknown suite `e` is assumed to take the average time (55ms).
+    assert_eq!(shards[0], ["Sharded suite (a)", "Sharded suite (c)"]);
+    assert_eq!(
+        shards[1],
+        [
+            "Sharded suite (b)",
+            "Sharded suite (d)",
+            "Sharded suite (e)"
+        ]
+    );
+}
+
+#[test]
+fn invalid_shards_are_rejected() {
+    for shard in ["0/3", "4/3", "1/0", "1", "a/b"] {
+        assert!(shard.parse::<Shard>().is_err(), "{shard} must be rejected");
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use e2e::{TestRunnerConfiguration, test_suite};
+
+#[derive(Debug, Clone, Default)]
+struct TestConfig {
+    observed: Arc<Mutex<Vec<String>>>,
+}
+
+#[derive(Debug)]
+struct StatefulFlow {
+    config: TestConfig,
+    session: Option<String>,
+    before_each_calls: usize,
+    items: Vec<u32>,
+}
+
+#[test_suite("Stateful suite")]
+impl StatefulFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            config: c.clone(),
+            session: None,
+            before_each_calls: 0,
+            items: Vec::new(),
+        })
+    }
+
+    #[before_all]
+    async fn before_all(&mut self) -> anyhow::Result<()> {
+        self.session = Some("session-1".to_string());
+        Ok(())
+    }
+
+    #[before_each]
+    async fn before_each(&mut self) -> anyhow::Result<()> {
+        self.before_each_calls += 1;
+        Ok(())
+    }
+
+    #[test_case("Create item")]
+    async fn create_item(&mut self) -> anyhow::Result<()> {
+        anyhow::ensure!(self.session.is_some(), "Session must be set by before_all");
+        self.items.push(42);
+        Ok(())
+    }
+
+    #[test_case("Read item")]
+    async fn read_item(&self) -> anyhow::Result<()> {
+        self.config.observed.lock().unwrap().push(format!(
+            "{:?} {:?} {}",
+            self.session, self.items, self.before_each_calls
+        ));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn state_is_shared_between_hooks_and_tests() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone());
+    tester.add_suite(StatefulFlow::new());
+    tester.run().await.unwrap();
+
+    assert_eq!(
+        *config.observed.lock().unwrap(),
+        [r#"Some("session-1") [42] 2"#]
+    );
+}
+
+#[derive(Debug)]
+struct TimingOutFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Timing out suite")]
+impl TimingOutFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self { config: c.clone() })
+    }
+
+    #[after_each]
+    async fn after_each(&mut self) -> anyhow::Result<()> {
+        self.config
+            .observed
+            .lock()
+            .unwrap()
+            .push("after_each".to_string());
+        Ok(())
+    }
+
+    #[test_case("Hangs")]
+    async fn hangs(&self) -> anyhow::Result<()> {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn timed_out_tests_release_the_suite() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_runner_config(
+        TestRunnerConfiguration::default().with_timeout(Duration::from_millis(100)),
+    );
+    tester.add_suite(TimingOutFlow::new());
+    tokio::time::timeout(Duration::from_secs(5), tester.run())
+        .await
+        .expect("after_each waited for the timed out test")
+        .unwrap();
+
+    assert_eq!(*config.observed.lock().unwrap(), ["after_each"]);
+}
@@ -0,0 +1,70 @@
+use std::sync::Mutex;
+
+use e2e::{TestRunnerConfiguration, test_suite};
+
+static EXECUTED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone)]
+struct TestConfig;
+
+#[derive(Debug, Clone)]
+struct TestFlow;
+
+#[test_suite("Shuffled suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("1")]
+    async fn test_1(&self) -> anyhow::Result<()> {
+        EXECUTED.lock().unwrap().push("1");
+        Ok(())
+    }
+
+    #[test_case("2")]
+    async fn test_2(&self) -> anyhow::Result<()> {
+        EXECUTED.lock().unwrap().push("2");
+        Ok(())
+    }
+
+    #[test_case("3")]
+    async fn test_3(&self) -> anyhow::Result<()> {
+        EXECUTED.lock().unwrap().push("3");
+        Ok(())
+    }
+
+    #[test_case("4")]
+    async fn test_4(&self) -> anyhow::Result<()> {
+        EXECUTED.lock().unwrap().push("4");
+        Ok(())
+    }
+
+    #[test_case("5")]
+    async fn test_5(&self) -> anyhow::Result<()> {
+        EXECUTED.lock().unwrap().push("5");
+        Ok(())
+    }
+}
+
+async fn run_with_seed(seed: u64) -> Vec<&'static str> {
+    let config = TestRunnerConfiguration::default()
+        .with_shuffle(true)
+        .with_seed(seed);
+    let mut tester = e2e::TestRunner::new(TestConfig).with_runner_config(config);
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+    std::mem::take(&mut *EXECUTED.lock().unwrap())
+}
+
+#[tokio::test]
+async fn shuffle_is_reproducible() {
+    let order = run_with_seed(42).await;
+    assert_eq!(run_with_seed(42).await, order);
+
+    let mut sorted = order.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, ["1", "2", "3", "4", "5"]);
+    assert_ne!(order, sorted, "Order must be shuffled");
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use std::time::Duration;
+
+use e2e::{LogCaptureLayer, TestRunnerConfiguration, test_suite};
+use tracing_subscriber::layer::SubscriberExt as _;
+
+use self::common::{ResultsReporter, TestConfig};
+
+/// Logs an event when dropped, i.e. when the task owning it is aborted.
+struct DropGuard(TestConfig, &'static str);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.log(self.1);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TestFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Suite with background tasks")]
+impl TestFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        let config = c.clone();
+        let token = e2e::cancellation_token();
+        e2e::spawn(async move {
+            token.cancelled().await;
+            config.log("suite task stopped");
+        });
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Timed out")]
+    async fn timed_out(&self) -> anyhow::Result<()> {
+        let config = self.config.clone();
+        let token = e2e::cancellation_token();
+        e2e::spawn(async move {
+            token.cancelled().await;
+            config.log("test task stopped");
+        });
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        Ok(())
+    }
+
+    #[test_case("Stubborn")]
+    async fn stubborn(&self) -> anyhow::Result<()> {
+        let guard = DropGuard(self.config.clone(), "stubborn task aborted");
+        e2e::spawn(async move {
+            let _guard = guard;
+            // Ignores cancellation.
+            std::future::pending::<()>().await;
+        });
+        self.config.log("stubborn test finished");
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LoggingFlow;
+
+#[test_suite("Suite with logging tasks")]
+impl LoggingFlow {
+    #[constructor]
+    async fn new(_c: &TestConfig) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    #[test_case("Logs from a task")]
+    async fn logs_from_task(&self) -> anyhow::Result<()> {
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        e2e::spawn(async move {
+            tracing::info!("Hello from a task");
+            let _ = done_tx.send(());
+        });
+        done_rx.await?;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn spawned_tasks_are_stopped_with_their_owner() {
+    let config = TestConfig::default();
+    let mut tester = e2e::TestRunner::new(config.clone()).with_runner_config(
+        TestRunnerConfiguration::default()
+            .with_timeout(Duration::from_millis(100))
+            .with_spawned_tasks_timeout(Duration::from_millis(100)),
+    );
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+
+    assert_eq!(
+        config.events.get(),
+        [
+            "test task stopped",
+            "stubborn test finished",
+            "stubborn task aborted",
+            "suite task stopped",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn logs_of_spawned_tasks_are_captured_with_their_owner() {
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(LogCaptureLayer));
+
+    let (reporter, results) = ResultsReporter::new();
+    let mut tester = e2e::TestRunner::new(TestConfig::default()).with_reporter(Box::new(reporter));
+    tester.add_suite(LoggingFlow::new());
+    tester.run().await.unwrap();
+
+    let results = results.lock().unwrap();
+    let logs = &results[0].tests[0].logs;
+    assert!(
+        logs.iter().any(|line| line.ends_with("Hello from a task")),
+        "{logs:?}"
+    );
+}
@@ -0,0 +1,100 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use e2e::{TestContext, TestRunnerConfiguration, test_suite};
+
+#[derive(Debug, Clone, Default)]
+struct TestConfig {
+    fail: bool,
+    dirs: Arc<Mutex<Vec<(&'static str, PathBuf)>>>,
+}
+
+impl TestConfig {
+    fn record(&self, owner: &'static str, dir: PathBuf) {
+        self.dirs.lock().unwrap().push((owner, dir));
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TestFlow {
+    config: TestConfig,
+}
+
+#[test_suite("Temp dir suite")]
+impl TestFlow {
+    #[constructor]
+    async fn new(c: &TestConfig) -> anyhow::Result<Self> {
+        let dir = e2e::temp_dir()?;
+        std::fs::write(dir.join("suite.txt"), "suite")?;
+        c.record("suite", dir);
+        Ok(Self { config: c.clone() })
+    }
+
+    #[test_case("Passing")]
+    async fn passing(&self, ctx: &TestContext) -> anyhow::Result<()> {
+        let dir = ctx.temp_dir()?;
+        std::fs::write(dir.join("passing.txt"), "passing")?;
+        self.config.record("passing", dir.to_path_buf());
+        Ok(())
+    }
+
+    #[test_case("Maybe failing")]
+    async fn maybe_failing(&self) -> anyhow::Result<()> {
+        let dir = e2e::temp_dir()?;
+        std::fs::write(dir.join("failing.txt"), "failing")?;
+        self.config.record("maybe failing", dir);
+        anyhow::ensure!(!self.config.fail, "Expected failure");
+        Ok(())
+    }
+}
+
+/// Runs the suite, returning temp dirs with whether they still exist.
+async fn run(config: TestConfig, keep_temp: bool) -> Vec<(&'static str, bool)> {
+    let mut tester = e2e::TestRunner::new(config.clone())
+        .with_runner_config(TestRunnerConfiguration::default().with_keep_temp(keep_temp));
+    tester.add_suite(TestFlow::new());
+    tester.run().await.unwrap();
+
+    let dirs = config.dirs.lock().unwrap().clone();
+    let exist = dirs
+        .iter()
+        .map(|(owner, dir)| (*owner, dir.exists()))
+        .collect();
+    // Suite temp dir is located in the temp dir of the run.
+    let _ = std::fs::remove_dir_all(dirs[0].1.parent().unwrap());
+    exist
+}
+
+#[tokio::test]
+async fn temp_dirs_are_kept_on_failure() {
+    let config = TestConfig {
+        fail: true,
+        ..TestConfig::default()
+    };
+    assert_eq!(
+        run(config, false).await,
+        [("suite", true), ("passing", false), ("maybe failing", true)]
+    );
+}
+
+#[tokio::test]
+async fn temp_dirs_are_removed_on_success() {
+    assert_eq!(
+        run(TestConfig::default(), false).await,
+        [
+            ("suite", false),
+            ("passing", false),
+            ("maybe failing", false)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn temp_dirs_are_kept_with_keep_temp() {
+    assert_eq!(
+        run(TestConfig::default(), true).await,
+        [("suite", true), ("passing", true), ("maybe failing", true)]
+    );
+}